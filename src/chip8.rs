@@ -1,40 +1,364 @@
 pub mod cpu;
+pub mod crt;
+pub mod flicker;
+pub mod framebuffer;
+pub mod frontend;
+pub mod keymap;
+pub mod keypad;
+pub mod osd;
+pub mod random;
+pub mod raster;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod cheats;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod debugger;
+#[cfg(feature = "std")]
+pub mod decompiler;
+#[cfg(feature = "sdl")]
 pub mod display;
+#[cfg(feature = "std")]
+pub mod expression;
+#[cfg(feature = "std")]
+pub mod headless;
+#[cfg(feature = "std")]
+pub mod lint;
+#[cfg(feature = "std")]
+pub mod recording;
+#[cfg(feature = "std")]
+pub mod screenshot;
+#[cfg(feature = "sdl")]
+pub mod sdl;
+#[cfg(feature = "std")]
 pub mod sound;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "tui")]
+pub mod terminal;
 
-use display::Display;
-
-use std::time::Duration;
+#[cfg(feature = "std")]
+use {
+    cheats::CheatEngine,
+    crt::CrtFilters,
+    debugger::{Debugger, DebuggerCommand},
+    flicker::FlickerFilter,
+    frontend::{AudioSink, HostAction, InputSource, VideoSink},
+    headless::STEPS_PER_FRAME,
+    keymap::Keymap,
+    raster::{Palette, Rasterizer, THEMES},
+    recording::GifRecorder,
+    std::path::PathBuf,
+    std::time::{Duration, Instant},
+};
+#[cfg(feature = "sdl")]
+use {display::Display, sdl::SdlInput, std::fs, std::path::Path};
 
-pub struct Emulator {}
+#[cfg(feature = "std")]
+pub struct Emulator {
+    pub debugger: Debugger,
+    pub cheats: CheatEngine,
+    pub cheat_directory: PathBuf,
+    // Zero runs as fast as the frontends allow.
+    pub steps_per_second: u32,
+    pub palette: Palette,
+    // Named palettes that the palette hotkey cycles through.
+    pub themes: Vec<(String, Palette)>,
+    // Window pixels per CHIP-8 pixel when the window opens.
+    pub window_scale: u32,
+    pub fullscreen: bool,
+    pub keymap: Keymap,
+    // Shows a clickable hex keypad next to the screen.
+    pub show_keypad: bool,
+    pub flicker_filter: FlickerFilter,
+    pub crt_filters: CrtFilters,
+    // Shown with the speed counters in the on-screen display.
+    pub platform: String,
+    pub show_counters: bool,
+    pub screenshot_directory: PathBuf,
+    pub recording_directory: PathBuf,
+    // Scale of screenshots and recordings.
+    pub capture_scale: usize,
+    recording: Option<(GifRecorder, u64)>,
+    // Messages for the on-screen display that have not been shown yet.
+    notices: Vec<String>,
+}
 
+#[cfg(feature = "std")]
 impl Emulator {
     pub fn initialize() -> Emulator {
-        Emulator {}
+        Emulator {
+            debugger: Debugger::new(),
+            cheats: CheatEngine::new(),
+            cheat_directory: PathBuf::from("cheats"),
+            steps_per_second: 360,
+            palette: Palette::default(),
+            themes: THEMES
+                .iter()
+                .map(|(name, palette)| (name.to_string(), *palette))
+                .collect(),
+            window_scale: 8,
+            fullscreen: false,
+            keymap: Keymap::default(),
+            show_keypad: false,
+            flicker_filter: FlickerFilter::Off,
+            crt_filters: CrtFilters::default(),
+            platform: String::from("CHIP-8"),
+            show_counters: false,
+            screenshot_directory: PathBuf::from("screenshots"),
+            recording_directory: PathBuf::from("recordings"),
+            capture_scale: 1,
+            recording: None,
+            notices: Vec::new(),
+        }
     }
 
+    #[cfg(feature = "sdl")]
     pub fn run_file(&mut self, filepath: &str) -> Result<(), String> {
         let sdl_context = sdl2::init().expect("Unable to initialize sdl");
-        let sound_controller = sound::build_sound_controller();
+        let mut sound_controller = sound::build_sound_controller();
+        let rom = fs::read(filepath).map_err(|e| e.to_string())?;
+        let title = match Path::new(filepath).file_stem() {
+            Some(name) => format!("CHIP-8 - {}", name.to_string_lossy()),
+            None => String::from("CHIP-8"),
+        };
+        let mut display = Display::new(&sdl_context, &title, self.window_scale, self.palette);
+        display.set_flicker_filter(self.flicker_filter);
+        display.set_crt_filters(self.crt_filters);
+        let mut input = SdlInput::new(&sdl_context)?;
+        input.set_keymap(self.keymap.clone());
+        if self.show_keypad {
+            display.show_keypad();
+            input.show_keypad(display.window_size());
+        }
+        if self.fullscreen {
+            display.set_fullscreen(true);
+        }
+        let result = self.run(&rom, &mut display, &mut sound_controller, &mut input);
+        self.print_notices();
+        result
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn run_terminal(&mut self, filepath: &str) -> Result<(), String> {
+        let rom = std::fs::read(filepath).map_err(|e| e.to_string())?;
+        let session = terminal::TerminalSession::start()?;
+        let mut video = terminal::TerminalVideo::new();
+        video.set_palette(self.palette);
+        let mut audio = terminal::TerminalAudio::new();
+        let mut input = session.input();
+        input.set_keymap(self.keymap.clone());
+        let result = self.run(&rom, &mut video, &mut audio, &mut input);
+        drop(session);
+        self.print_notices();
+        result
+    }
+
+    pub fn run<V, A, I>(
+        &mut self,
+        rom: &[u8],
+        video: &mut V,
+        audio: &mut A,
+        input: &mut I,
+    ) -> Result<(), String>
+    where
+        V: VideoSink + ?Sized,
+        A: AudioSink + ?Sized,
+        I: InputSource + ?Sized,
+    {
         let mut cpu = cpu::Cpu::initialize();
-        let mut display = Display::new(&sdl_context);
-        cpu.load_rom(filepath);
+        cpu.load_program(rom);
+        self.cheats.load_for_rom(&self.cheat_directory, rom)?;
+        let mut steps: u64 = 0;
+        let mut frames: u64 = 0;
+        // When the counters were last updated, and the steps and frames then.
+        let mut counted = (Instant::now(), steps, frames);
+        if let Some(osd) = video.osd() {
+            osd.profile = self.platform.clone();
+        }
 
         loop {
-            if cpu.check_key_events(&sdl_context) {
-                break;
-            };
+            match input.poll(&mut cpu) {
+                HostAction::Quit => break,
+                HostAction::Break => self.debugger.request_break(),
+                HostAction::Screenshot => {
+                    let frame = steps / STEPS_PER_FRAME as u64;
+                    self.save_screenshot(&cpu.graphics, frame);
+                }
+                HostAction::ToggleRecording if self.is_recording() => self.stop_recording(),
+                HostAction::ToggleRecording => self.start_recording(steps / STEPS_PER_FRAME as u64),
+                HostAction::ToggleFullscreen => {
+                    self.fullscreen = !self.fullscreen;
+                    video.set_fullscreen(self.fullscreen);
+                }
+                HostAction::NextPalette => {
+                    self.next_palette();
+                    video.set_palette(self.palette);
+                }
+                HostAction::ToggleCrt(effect) => {
+                    let enabled = self.crt_filters.toggle(effect);
+                    let state = if enabled { "on" } else { "off" };
+                    self.notify(format!("{} {}", effect.name(), state));
+                    video.set_crt_filters(self.crt_filters);
+                }
+                HostAction::ToggleCounters => {
+                    self.show_counters = !self.show_counters;
+                    if !self.show_counters {
+                        if let Some(osd) = video.osd() {
+                            osd.set_counters(None);
+                        }
+                    }
+                }
+                HostAction::None => {}
+            }
+            self.show_notices(video);
 
             let step_result = cpu.step();
-            if step_result.make_sound {
-                sound_controller.play();
-            } else {
-                sound_controller.stop();
+            if let Some(opcode) = step_result.unknown_opcode {
+                self.notify(format!("Unknown opcode 0x{:04X}", opcode));
             }
+            let make_sound = step_result.make_sound;
+            steps += 1;
+            audio.set_tone(make_sound);
+            audio.tick();
 
-            display.draw(step_result.graphics);
-            ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 360));
+            // The screen is only handed to the frontend once per 60 Hz frame,
+            // and only if DRW or CLS changed it.
+            if steps.is_multiple_of(STEPS_PER_FRAME as u64) {
+                // Frozen values are written back once per frame.
+                self.cheats.apply(&mut cpu);
+                if let Some((recorder, _)) = &mut self.recording {
+                    recorder.capture(&cpu.graphics);
+                }
+                if cpu.take_dirty() {
+                    video.present(&cpu.graphics);
+                }
+                video.set_keys(&cpu.key);
+                video.end_frame();
+                frames += 1;
+            }
+
+            let elapsed = counted.0.elapsed();
+            if self.show_counters && elapsed >= Duration::from_secs(1) {
+                let rate = |count: u64| (count as f64 / elapsed.as_secs_f64()).round() as u32;
+                let counters = (rate(frames - counted.2), rate(steps - counted.1));
+                if let Some(osd) = video.osd() {
+                    osd.set_counters(Some(counters));
+                }
+                counted = (Instant::now(), steps, frames);
+            }
+
+            let events = self.debugger.after_step(&cpu);
+            let stops = events.iter().any(|event| event.stops_execution());
+            if stops {
+                audio.set_tone(false);
+                self.notify(String::from("Paused"));
+                self.show_notices(video);
+                if cpu.take_dirty() {
+                    video.present(&cpu.graphics);
+                }
+                video.refresh();
+                // The debugger prompt needs the terminal back.
+                input.suspend();
+            }
+            for event in &events {
+                eprintln!("{}", self.debugger.describe(event));
+            }
+            if stops {
+                if let DebuggerCommand::Quit = self.debugger.interact(&mut cpu, &mut self.cheats) {
+                    break;
+                }
+                video.resume();
+                counted = (Instant::now(), steps, frames);
+            }
+
+            if let Some(nanoseconds) = 1_000_000_000u32.checked_div(self.steps_per_second) {
+                ::std::thread::sleep(Duration::new(0, nanoseconds));
+            }
+        }
+        audio.set_tone(false);
+        self.stop_recording();
+        if video.osd().is_none() {
+            self.print_notices();
         }
         Ok(())
     }
+
+    fn rasterizer(&self) -> Rasterizer {
+        let mut rasterizer = Rasterizer::new(self.capture_scale);
+        rasterizer.palette = self.palette;
+        rasterizer
+    }
+
+    fn save_screenshot(&mut self, graphics: &framebuffer::Framebuffer, frame: u64) {
+        let path = screenshot::screenshot_file(&self.screenshot_directory, frame);
+        match screenshot::save_png(&path, graphics, &self.rasterizer()) {
+            Ok(()) => self.notify(format!("Saved screenshot to {}", path.display())),
+            Err(error) => self.notify(format!("Unable to save screenshot: {}", error)),
+        }
+    }
+
+    // Switches to the theme after the current one, or to the first theme when
+    // the current palette is a custom one.
+    pub fn next_palette(&mut self) {
+        let next = self
+            .themes
+            .iter()
+            .position(|(_, palette)| *palette == self.palette)
+            .map_or(0, |index| (index + 1) % self.themes.len());
+        if let Some((name, palette)) = self.themes.get(next) {
+            self.palette = *palette;
+            let message = format!("Palette: {}", name);
+            self.notify(message);
+        }
+    }
+
+    // Reports on the on-screen display of the frontend, or on stderr for
+    // frontends without one.
+    fn notify(&mut self, message: String) {
+        self.notices.push(message);
+    }
+
+    fn show_notices<V: VideoSink + ?Sized>(&mut self, video: &mut V) {
+        match video.osd() {
+            Some(osd) => {
+                for notice in self.notices.drain(..) {
+                    osd.show(&notice);
+                }
+            }
+            None => self.print_notices(),
+        }
+    }
+
+    // Notices left when the frontend closed, e.g. about the saved recording.
+    fn print_notices(&mut self) {
+        for notice in self.notices.drain(..) {
+            eprintln!("{}", notice);
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // Starts capturing one frame per 60 Hz tick; `frame` names the file.
+    pub fn start_recording(&mut self, frame: u64) {
+        if !self.is_recording() {
+            self.recording = Some((GifRecorder::new(self.rasterizer()), frame));
+            self.notify(String::from("Recording started"));
+        }
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some((recorder, frame)) = self.recording.take() {
+            let path = recording::recording_file(&self.recording_directory, frame);
+            match recorder.save(&path) {
+                Ok(()) => self.notify(format!("Saved recording to {}", path.display())),
+                Err(error) => self.notify(format!("Unable to save recording: {}", error)),
+            }
+        }
+    }
 }
@@ -0,0 +1,410 @@
+use super::cpu::Instruction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+pub const PROGRAM_START: usize = 0x200;
+pub const MEMORY_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Fallthrough,
+    Jump,
+    Skip,
+    Call,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub source: usize,
+    pub target: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IValue {
+    Known(usize),
+    Varies,
+}
+
+impl IValue {
+    fn join(self, other: IValue) -> IValue {
+        if self == other {
+            self
+        } else {
+            IValue::Varies
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub instructions: Vec<(usize, Instruction)>,
+    pub successors: Vec<Edge>,
+}
+
+impl BasicBlock {
+    pub fn end(&self) -> usize {
+        self.last_address() + 2
+    }
+
+    pub fn last_address(&self) -> usize {
+        self.instructions
+            .last()
+            .map_or(self.start, |(address, _)| *address)
+    }
+
+    pub fn terminator(&self) -> Instruction {
+        self.instructions.last().expect("Blocks are never empty").1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfModification {
+    pub address: usize,
+    pub target: usize,
+    pub length: usize,
+}
+
+pub struct ControlFlowGraph {
+    pub memory: Vec<u8>,
+    pub rom_end: usize,
+    pub instructions: BTreeMap<usize, Instruction>,
+    pub edges: BTreeMap<usize, Vec<Edge>>,
+    pub blocks: BTreeMap<usize, BasicBlock>,
+    pub subroutines: BTreeSet<usize>,
+    pub computed_jumps: Vec<usize>,
+    pub exits: Vec<Edge>,
+    pub unreachable: Vec<(usize, usize)>,
+    pub self_modifying: Vec<SelfModification>,
+    i_values: BTreeMap<usize, IValue>,
+    // The start of the block each reachable instruction belongs to. Blocks
+    // can interleave when code jumps to odd addresses, so address ranges
+    // alone don't tell.
+    block_of: BTreeMap<usize, usize>,
+}
+
+pub fn decode_at(memory: &[u8], address: usize) -> Instruction {
+    Instruction::decode((memory[address] as u16) << 8 | memory[address + 1] as u16)
+}
+
+// Number of bytes an instruction writes to or reads from memory starting at I.
+pub fn memory_write_length(instruction: Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::StoreBcd(_) => Some(3),
+        Instruction::StoreRegisters(x) => Some(x + 1),
+        _ => None,
+    }
+}
+
+pub fn memory_read_length(instruction: Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::Draw(_, _, n) => Some(n),
+        Instruction::LoadRegisters(x) => Some(x + 1),
+        _ => None,
+    }
+}
+
+fn successors(address: usize, instruction: Instruction) -> Vec<Edge> {
+    let edge = |target, kind| Edge {
+        source: address,
+        target,
+        kind,
+    };
+    match instruction {
+        Instruction::Jump(nnn) => vec![edge(nnn, EdgeKind::Jump)],
+        Instruction::Call(nnn) => vec![
+            edge(nnn, EdgeKind::Call),
+            edge(address + 2, EdgeKind::Fallthrough),
+        ],
+        Instruction::Ret | Instruction::JumpV0(_) | Instruction::Unknown(_) => vec![],
+        skip if skip.is_skip() => vec![
+            edge(address + 2, EdgeKind::Fallthrough),
+            edge(address + 4, EdgeKind::Skip),
+        ],
+        _ => vec![edge(address + 2, EdgeKind::Fallthrough)],
+    }
+}
+
+impl ControlFlowGraph {
+    pub fn build(rom: &[u8]) -> ControlFlowGraph {
+        let mut memory = vec![0; MEMORY_SIZE];
+        let rom_end = (PROGRAM_START + rom.len()).min(MEMORY_SIZE);
+        memory[PROGRAM_START..rom_end].copy_from_slice(&rom[..rom_end - PROGRAM_START]);
+
+        let mut graph = ControlFlowGraph {
+            memory,
+            rom_end,
+            instructions: BTreeMap::new(),
+            edges: BTreeMap::new(),
+            blocks: BTreeMap::new(),
+            subroutines: BTreeSet::new(),
+            computed_jumps: Vec::new(),
+            exits: Vec::new(),
+            unreachable: Vec::new(),
+            self_modifying: Vec::new(),
+            i_values: BTreeMap::new(),
+            block_of: BTreeMap::new(),
+        };
+        graph.discover();
+        graph.build_blocks();
+        graph.propagate_i();
+        graph.find_self_modifications();
+        graph.find_unreachable();
+        graph
+    }
+
+    pub fn contains(&self, address: usize) -> bool {
+        address >= PROGRAM_START && address + 1 < self.rom_end
+    }
+
+    fn discover(&mut self) {
+        let mut worklist = vec![PROGRAM_START];
+        while let Some(address) = worklist.pop() {
+            if self.instructions.contains_key(&address) || !self.contains(address) {
+                continue;
+            }
+
+            let instruction = decode_at(&self.memory, address);
+            match instruction {
+                Instruction::Call(nnn) => {
+                    self.subroutines.insert(nnn);
+                }
+                Instruction::JumpV0(_) => self.computed_jumps.push(address),
+                _ => {}
+            }
+
+            let (inside, outside): (Vec<Edge>, Vec<Edge>) = successors(address, instruction)
+                .into_iter()
+                .partition(|edge| self.contains(edge.target));
+            self.exits.extend(outside);
+            worklist.extend(inside.iter().map(|edge| edge.target));
+
+            self.instructions.insert(address, instruction);
+            self.edges.insert(address, inside);
+        }
+        self.computed_jumps.sort_unstable();
+    }
+
+    fn build_blocks(&mut self) {
+        let mut leaders = BTreeSet::new();
+        leaders.insert(PROGRAM_START);
+        for edges in self.edges.values() {
+            let straight_line = edges.len() == 1 && edges[0].kind == EdgeKind::Fallthrough;
+            if !straight_line {
+                leaders.extend(edges.iter().map(|edge| edge.target));
+            }
+        }
+        leaders.retain(|address| self.instructions.contains_key(address));
+
+        for leader in &leaders {
+            let mut address = *leader;
+            let mut instructions = vec![(address, self.instructions[&address])];
+            loop {
+                let edges = &self.edges[&address];
+                let next = address + 2;
+                let continues = edges.len() == 1
+                    && edges[0].kind == EdgeKind::Fallthrough
+                    && !leaders.contains(&next);
+                if !continues {
+                    break;
+                }
+                address = next;
+                instructions.push((address, self.instructions[&address]));
+            }
+            for (address, _) in &instructions {
+                self.block_of.insert(*address, *leader);
+            }
+
+            self.blocks.insert(
+                *leader,
+                BasicBlock {
+                    start: *leader,
+                    instructions,
+                    successors: self.edges[&address].clone(),
+                },
+            );
+        }
+    }
+
+    // Tracks the value of I across the program so that memory accesses through
+    // I can be checked. A call clobbers I for the code after the call.
+    fn propagate_i(&mut self) {
+        if !self.instructions.contains_key(&PROGRAM_START) {
+            return;
+        }
+        let mut worklist = vec![(PROGRAM_START, IValue::Known(0))];
+        while let Some((address, incoming)) = worklist.pop() {
+            let value = match self.i_values.get(&address) {
+                Some(current) => {
+                    let joined = current.join(incoming);
+                    if joined == *current {
+                        continue;
+                    }
+                    joined
+                }
+                None => incoming,
+            };
+            self.i_values.insert(address, value);
+
+            let outgoing = match self.instructions[&address] {
+                Instruction::LoadI(nnn) => IValue::Known(nnn),
+                Instruction::AddI(_) | Instruction::LoadFont(_) => IValue::Varies,
+                _ => value,
+            };
+            for edge in &self.edges[&address] {
+                let value = match (self.instructions[&address], edge.kind) {
+                    (Instruction::Call(_), EdgeKind::Fallthrough) => IValue::Varies,
+                    _ => outgoing,
+                };
+                worklist.push((edge.target, value));
+            }
+        }
+    }
+
+    pub fn known_i(&self, address: usize) -> Option<usize> {
+        match self.i_values.get(&address) {
+            Some(IValue::Known(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn find_self_modifications(&mut self) {
+        for (address, instruction) in &self.instructions {
+            let length = match memory_write_length(*instruction) {
+                Some(length) => length,
+                None => continue,
+            };
+            let target = match self.known_i(*address) {
+                Some(target) => target,
+                None => continue,
+            };
+            let overlaps = self
+                .instructions
+                .range(target.saturating_sub(1)..target + length)
+                .next()
+                .is_some();
+            if overlaps {
+                self.self_modifying.push(SelfModification {
+                    address: *address,
+                    target,
+                    length,
+                });
+            }
+        }
+    }
+
+    fn find_unreachable(&mut self) {
+        let mut covered = vec![false; MEMORY_SIZE];
+        for address in self.instructions.keys() {
+            covered[*address] = true;
+            covered[*address + 1] = true;
+        }
+
+        let mut start = None;
+        let covered = covered[PROGRAM_START..self.rom_end]
+            .iter()
+            .chain([true].iter());
+        for (address, reachable) in (PROGRAM_START..).zip(covered) {
+            match (start, *reachable) {
+                (None, false) => start = Some(address),
+                (Some(region_start), true) => {
+                    self.unreachable.push((region_start, address));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Instructions reachable from a function entry without following calls.
+    pub fn function_body(&self, entry: usize) -> BTreeSet<usize> {
+        let mut body = BTreeSet::new();
+        let mut worklist = vec![entry];
+        while let Some(address) = worklist.pop() {
+            if !self.instructions.contains_key(&address) || !body.insert(address) {
+                continue;
+            }
+            for edge in &self.edges[&address] {
+                if edge.kind != EdgeKind::Call {
+                    worklist.push(edge.target);
+                }
+            }
+        }
+        body
+    }
+
+    pub fn block_containing(&self, address: usize) -> Option<&BasicBlock> {
+        self.block_of
+            .get(&address)
+            .and_then(|start| self.blocks.get(start))
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph chip8 {\n");
+        dot += "    node [shape=box, fontname=\"monospace\"];\n";
+
+        for block in self.blocks.values() {
+            let mut label = String::new();
+            for (address, instruction) in &block.instructions {
+                write!(label, "0x{:03X}: {}\\l", address, instruction).unwrap();
+            }
+            let mut attributes = format!("label=\"{}\"", label);
+            if self.subroutines.contains(&block.start) {
+                attributes += ", peripheries=2";
+            }
+            let modifies_code = self.self_modifying.iter().any(|modification| {
+                modification.address >= block.start && modification.address < block.end()
+            });
+            if modifies_code {
+                attributes += ", color=red";
+            }
+            writeln!(dot, "    b{:03X} [{}];", block.start, attributes).unwrap();
+        }
+
+        for block in self.blocks.values() {
+            for edge in &block.successors {
+                let style = match edge.kind {
+                    EdgeKind::Fallthrough => "",
+                    EdgeKind::Jump => " [label=\"jump\"]",
+                    EdgeKind::Skip => " [label=\"skip\"]",
+                    EdgeKind::Call => " [label=\"call\", style=dashed]",
+                };
+                writeln!(
+                    dot,
+                    "    b{:03X} -> b{:03X}{};",
+                    block.start, edge.target, style
+                )
+                .unwrap();
+            }
+        }
+
+        for address in &self.computed_jumps {
+            writeln!(
+                dot,
+                "    u{:03X} [shape=diamond, label=\"{}\\nunresolved\"];",
+                address, self.instructions[address]
+            )
+            .unwrap();
+            if let Some(block) = self.block_containing(*address) {
+                writeln!(
+                    dot,
+                    "    b{:03X} -> u{:03X} [style=dotted];",
+                    block.start, address
+                )
+                .unwrap();
+            }
+        }
+
+        if !self.unreachable.is_empty() {
+            let mut label = String::from("unreachable\\l");
+            for (start, end) in &self.unreachable {
+                write!(label, "0x{:03X}-0x{:03X}\\l", start, end - 1).unwrap();
+            }
+            writeln!(dot, "    unreachable [shape=note, label=\"{}\"];", label).unwrap();
+        }
+
+        dot += "}\n";
+        dot
+    }
+}
@@ -0,0 +1,305 @@
+use super::cpu::Cpu;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Location {
+    Memory(usize),
+    Register(usize),
+}
+
+impl Location {
+    pub fn parse(text: &str) -> Result<Location, String> {
+        let text = text.trim().to_ascii_lowercase();
+        if text.len() == 2 && text.starts_with('v') {
+            return usize::from_str_radix(&text[1..], 16)
+                .map(Location::Register)
+                .map_err(|_| format!("Invalid register `{}`.", text));
+        }
+
+        let address = parse_number(&text)? as usize;
+        if address >= 4096 {
+            return Err(format!("Address `{}` is outside of memory.", text));
+        }
+        Ok(Location::Memory(address))
+    }
+
+    pub fn read(self, cpu: &Cpu) -> u8 {
+        match self {
+            Location::Memory(address) => cpu.memory[address],
+            Location::Register(register) => cpu.v[register],
+        }
+    }
+
+    pub fn write(self, cpu: &mut Cpu, value: u8) {
+        match self {
+            Location::Memory(address) => cpu.memory[address] = value,
+            Location::Register(register) => cpu.v[register] = value,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Location::Memory(address) => write!(f, "0x{:03X}", address),
+            Location::Register(register) => write!(f, "v{:X}", register),
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Result<u32, String> {
+    let text = text.trim();
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse::<u32>(),
+    };
+    parsed.map_err(|_| format!("Invalid number `{}`.", text))
+}
+
+fn parse_value(text: &str) -> Result<u8, String> {
+    let value = parse_number(text)?;
+    if value > 0xFF {
+        return Err(format!("Value `{}` does not fit into a byte.", text));
+    }
+    Ok(value as u8)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchFilter {
+    Equal(u8),
+    Increased,
+    Decreased,
+    Unchanged,
+    Changed,
+}
+
+impl SearchFilter {
+    pub fn parse(text: &str) -> Result<SearchFilter, String> {
+        let mut parts = text.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("eq"), Some(value)) => Ok(SearchFilter::Equal(parse_value(value)?)),
+            (Some("inc"), None) => Ok(SearchFilter::Increased),
+            (Some("dec"), None) => Ok(SearchFilter::Decreased),
+            (Some("same"), None) => Ok(SearchFilter::Unchanged),
+            (Some("changed"), None) => Ok(SearchFilter::Changed),
+            _ => Err(String::from(
+                "Expected one of: eq N, inc, dec, same, changed.",
+            )),
+        }
+    }
+
+    fn matches(self, old: u8, new: u8) -> bool {
+        match self {
+            SearchFilter::Equal(value) => new == value,
+            SearchFilter::Increased => new > old,
+            SearchFilter::Decreased => new < old,
+            SearchFilter::Unchanged => new == old,
+            SearchFilter::Changed => new != old,
+        }
+    }
+}
+
+// Narrows down candidate locations by comparing them against the values
+// recorded at the previous search.
+pub struct MemorySearch {
+    candidates: Vec<(Location, u8)>,
+}
+
+impl MemorySearch {
+    pub fn new(cpu: &Cpu) -> MemorySearch {
+        let memory = (0..cpu.memory.len()).map(Location::Memory);
+        let registers = (0..cpu.v.len()).map(Location::Register);
+        MemorySearch {
+            candidates: memory
+                .chain(registers)
+                .map(|location| (location, location.read(cpu)))
+                .collect(),
+        }
+    }
+
+    pub fn filter(&mut self, cpu: &Cpu, filter: SearchFilter) -> usize {
+        self.candidates = self
+            .candidates
+            .iter()
+            .map(|(location, old)| (*location, *old, location.read(cpu)))
+            .filter(|(_, old, new)| filter.matches(*old, *new))
+            .map(|(location, _, new)| (location, new))
+            .collect();
+        self.candidates.len()
+    }
+
+    pub fn candidates(&self) -> &[(Location, u8)] {
+        &self.candidates
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Freeze {
+    pub location: Location,
+    pub value: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheatSet {
+    pub freezes: Vec<Freeze>,
+}
+
+impl CheatSet {
+    pub fn freeze(&mut self, location: Location, value: u8) {
+        self.unfreeze(location);
+        self.freezes.push(Freeze { location, value });
+        self.freezes.sort_by_key(|freeze| freeze.location);
+    }
+
+    pub fn unfreeze(&mut self, location: Location) -> bool {
+        let count = self.freezes.len();
+        self.freezes.retain(|freeze| freeze.location != location);
+        count != self.freezes.len()
+    }
+
+    pub fn apply(&self, cpu: &mut Cpu) {
+        for freeze in &self.freezes {
+            freeze.location.write(cpu, freeze.value);
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<CheatSet, String> {
+        let mut cheats = CheatSet::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(location), Some(value)) => {
+                    cheats.freeze(Location::parse(location)?, parse_value(value)?)
+                }
+                _ => return Err(format!("Line {}: expected `LOCATION = VALUE`.", number + 1)),
+            }
+        }
+        Ok(cheats)
+    }
+
+    pub fn load(path: &Path) -> Result<CheatSet, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        CheatSet::parse(&text)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, self.to_string()).map_err(|e| e.to_string())
+    }
+}
+
+impl fmt::Display for CheatSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for freeze in &self.freezes {
+            writeln!(f, "{} = 0x{:02X}", freeze.location, freeze.value)?;
+        }
+        Ok(())
+    }
+}
+
+// 64 bit FNV-1a, stable across platforms and compiler versions.
+pub fn rom_hash(rom: &[u8]) -> u64 {
+    rom.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+pub fn cheat_file(directory: &Path, rom: &[u8]) -> PathBuf {
+    directory.join(format!("{:016x}.cht", rom_hash(rom)))
+}
+
+// Interactive front end for the debugger prompt.
+#[derive(Default)]
+pub struct CheatEngine {
+    pub cheats: CheatSet,
+    pub file: Option<PathBuf>,
+    search: Option<MemorySearch>,
+}
+
+const MAX_LISTED_CANDIDATES: usize = 20;
+
+impl CheatEngine {
+    pub fn new() -> CheatEngine {
+        CheatEngine::default()
+    }
+
+    pub fn load_for_rom(&mut self, directory: &Path, rom: &[u8]) -> Result<(), String> {
+        let file = cheat_file(directory, rom);
+        if file.exists() {
+            self.cheats = CheatSet::load(&file)?;
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    pub fn apply(&self, cpu: &mut Cpu) {
+        self.cheats.apply(cpu);
+    }
+
+    pub fn execute(&mut self, command: &str, argument: &str, cpu: &mut Cpu) -> Option<String> {
+        let result = match command {
+            "snapshot" => {
+                self.search = Some(MemorySearch::new(cpu));
+                Ok(String::from("Snapshot taken."))
+            }
+            "search" => self.search(argument, cpu),
+            "freeze" => {
+                let mut parts = argument.split_whitespace();
+                match (parts.next(), parts.next()) {
+                    (Some(location), Some(value)) => Location::parse(location)
+                        .and_then(|location| Ok((location, parse_value(value)?)))
+                        .map(|(location, value)| {
+                            self.cheats.freeze(location, value);
+                            self.cheats.apply(cpu);
+                            format!("Froze {} at 0x{:02X}.", location, value)
+                        }),
+                    _ => Err(String::from("Usage: freeze LOCATION VALUE")),
+                }
+            }
+            "unfreeze" => Location::parse(argument).map(|location| {
+                if self.cheats.unfreeze(location) {
+                    format!("Unfroze {}.", location)
+                } else {
+                    format!("{} was not frozen.", location)
+                }
+            }),
+            "cheats" => Ok(self.cheats.to_string()),
+            "save" => match &self.file {
+                Some(file) => self
+                    .cheats
+                    .save(file)
+                    .map(|_| format!("Cheats saved to {}.", file.display())),
+                None => Err(String::from("No cheat file for this rom.")),
+            },
+            _ => return None,
+        };
+        Some(result.unwrap_or_else(|error| error))
+    }
+
+    fn search(&mut self, argument: &str, cpu: &Cpu) -> Result<String, String> {
+        let filter = SearchFilter::parse(argument)?;
+        if self.search.is_none() {
+            if let SearchFilter::Equal(_) = filter {
+                self.search = Some(MemorySearch::new(cpu));
+            } else {
+                return Err(String::from("Take a snapshot before comparing values."));
+            }
+        }
+        let search = self.search.as_mut().expect("Search was just initialized.");
+        let remaining = search.filter(cpu, filter);
+
+        let mut output = format!("{} candidates left.", remaining);
+        for (location, value) in search.candidates().iter().take(MAX_LISTED_CANDIDATES) {
+            output += &format!("\n  {} = 0x{:02X}", location, value);
+        }
+        Ok(output)
+    }
+}
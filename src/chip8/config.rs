@@ -0,0 +1,207 @@
+use super::keymap::{KeyMatch, Keymap};
+use super::raster::{parse_color, Palette, THEMES};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_CONFIG_FILE: &str = "chip8.toml";
+
+// The subset of TOML the emulator needs: `[section]` headers, whose dotted
+// parts may be quoted, and `key = value` lines, where values may be quoted
+// and arrays may span several lines. Keys before the first header belong to
+// the section "".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        let mut lines = text.lines().enumerate();
+        while let Some((number, line)) = lines.next() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = section_name(header);
+                config.sections.entry(section.clone()).or_default();
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => {
+                    let mut value = value.trim().to_string();
+                    if value.starts_with('[') {
+                        while !value.ends_with(']') {
+                            let (_, line) = lines.next().ok_or_else(|| {
+                                format!("Line {}: the array is not closed with `]`.", number + 1)
+                            })?;
+                            value.push(' ');
+                            value.push_str(strip_comment(line).trim());
+                        }
+                    }
+                    config
+                        .sections
+                        .entry(section.clone())
+                        .or_default()
+                        .insert(unquote(key.trim()).to_string(), unquote(&value).to_string());
+                }
+                _ => {
+                    return Err(format!(
+                        "Line {}: expected `[SECTION]` or `KEY = VALUE`.",
+                        number + 1
+                    ))
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn section(&self, name: &str) -> Option<&BTreeMap<String, String>> {
+        self.sections.get(name)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?.get(key).map(String::as_str)
+    }
+
+    // Sections named `PREFIX.NAME`, e.g. `[theme.gameboy]`, with their names.
+    pub fn subsections<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a BTreeMap<String, String>)> + 'a {
+        self.sections.iter().filter_map(move |(name, keys)| {
+            let name = name.strip_prefix(prefix)?.strip_prefix('.')?;
+            Some((name, keys))
+        })
+    }
+
+    // The built-in themes followed by the ones defined in `[theme.NAME]`
+    // sections, which start from `base` (classic by default) and override
+    // single colours.
+    pub fn themes(&self) -> Result<Vec<(String, Palette)>, String> {
+        let mut themes: Vec<(String, Palette)> = THEMES
+            .iter()
+            .map(|(name, palette)| (name.to_string(), *palette))
+            .collect();
+        for (name, keys) in self.subsections("theme") {
+            let palette =
+                configure_palette(&themes, keys).map_err(|e| format!("[theme.{}]: {}", name, e))?;
+            match themes.iter_mut().find(|(theme, _)| theme == name) {
+                Some(theme) => theme.1 = palette,
+                None => themes.push((name.to_string(), palette)),
+            }
+        }
+        Ok(themes)
+    }
+
+    // The palette chosen in the `[palette]` section, if there is one.
+    pub fn palette(&self, themes: &[(String, Palette)]) -> Result<Option<Palette>, String> {
+        match self.section("palette") {
+            Some(keys) => configure_palette(themes, keys)
+                .map(Some)
+                .map_err(|e| format!("[palette]: {}", e)),
+            None => Ok(None),
+        }
+    }
+
+    // The standard keymap changed by the `[keymap]` section, and then by the
+    // `[keymap.ROM]` section for the ROM file without its extension. `match`
+    // in the `[input]` section picks scancodes or keycodes.
+    pub fn keymap(&self, rom: Option<&str>) -> Result<Keymap, String> {
+        let mut keymap = Keymap::default();
+        if let Some(matching) = self.get("input", "match") {
+            keymap.matching = KeyMatch::parse(matching).map_err(|e| format!("[input]: {}", e))?;
+        }
+        let mut sections = vec![String::from("keymap")];
+        sections.extend(rom.map(|rom| format!("keymap.{}", rom)));
+        for name in sections {
+            for (key, names) in self.section(&name).into_iter().flatten() {
+                keymap
+                    .configure(key, names)
+                    .map_err(|e| format!("[{}]: {}", name, e))?;
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+pub fn find_theme(themes: &[(String, Palette)], name: &str) -> Result<Palette, String> {
+    themes
+        .iter()
+        .find(|(theme, _)| theme.eq_ignore_ascii_case(name))
+        .map(|(_, palette)| *palette)
+        .ok_or_else(|| {
+            let names: Vec<&str> = themes.iter().map(|(theme, _)| theme.as_str()).collect();
+            format!(
+                "Unknown palette `{}`. Available: {}.",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+fn configure_palette(
+    themes: &[(String, Palette)],
+    keys: &BTreeMap<String, String>,
+) -> Result<Palette, String> {
+    let base = keys
+        .get("base")
+        .or_else(|| keys.get("theme"))
+        .map_or("classic", String::as_str);
+    let mut palette = find_theme(themes, base)?;
+    for (key, value) in keys {
+        let target = match key.as_str() {
+            "base" | "theme" => continue,
+            "background" => &mut palette.colors[0],
+            "foreground" => &mut palette.colors[1],
+            "plane2" => &mut palette.colors[2],
+            "overlap" => &mut palette.colors[3],
+            "grid" => &mut palette.grid,
+            _ => return Err(format!("Unknown key `{}`.", key)),
+        };
+        *target =
+            parse_color(value).ok_or_else(|| format!("`{}` is not a `#RRGGBB` colour.", value))?;
+    }
+    Ok(palette)
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (index, character) in line.char_indices() {
+        match character {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+// Joins the dotted parts of a header without their quotes, so
+// `keymap."Space Invaders"` is the section `keymap.Space Invaders`.
+fn section_name(header: &str) -> String {
+    let mut quoted = false;
+    let mut name = String::new();
+    for character in header.chars() {
+        match character {
+            '"' => quoted = !quoted,
+            _ if character.is_whitespace() && !quoted => {}
+            _ => name.push(character),
+        }
+    }
+    name
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
@@ -1,13 +1,15 @@
-use rand::Rng;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use super::framebuffer::{Framebuffer, HEIGHT};
+use super::random::RandomSource;
+#[cfg(feature = "std")]
+use super::random::ThreadRandom;
+#[cfg(not(feature = "std"))]
+use super::random::XorShiftRandom;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::fs;
-enum KeyActions {
-    Quit,
-    KeyUpDown(usize),
-    None,
-}
-
 enum ProgramCounterAction {
     Skip,
     Advance,
@@ -15,9 +17,113 @@ enum ProgramCounterAction {
     Jump(usize),
 }
 
-pub struct StepResult {
-    pub graphics: [[u8; 64]; 32],
+// Borrows the framebuffer instead of copying it; drop the result before
+// touching the CPU again.
+pub struct StepResult<'a> {
+    pub graphics: &'a Framebuffer,
     pub make_sound: bool,
+    pub unknown_opcode: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Cls,
+    Ret,
+    Sys(usize),
+    Jump(usize),
+    Call(usize),
+    SkipEqualByte(usize, u8),
+    SkipNotEqualByte(usize, u8),
+    SkipEqual(usize, usize),
+    LoadByte(usize, u8),
+    AddByte(usize, u8),
+    Load(usize, usize),
+    Or(usize, usize),
+    And(usize, usize),
+    Xor(usize, usize),
+    Add(usize, usize),
+    Sub(usize, usize),
+    ShiftRight(usize, usize),
+    SubN(usize, usize),
+    ShiftLeft(usize, usize),
+    SkipNotEqual(usize, usize),
+    LoadI(usize),
+    JumpV0(usize),
+    Random(usize, u8),
+    Draw(usize, usize, usize),
+    SkipKeyPressed(usize),
+    SkipKeyNotPressed(usize),
+    LoadDelayTimer(usize),
+    WaitKey(usize),
+    SetDelayTimer(usize),
+    SetSoundTimer(usize),
+    AddI(usize),
+    LoadFont(usize),
+    StoreBcd(usize),
+    StoreRegisters(usize),
+    LoadRegisters(usize),
+    Unknown(u16),
+}
+
+impl Instruction {
+    pub fn decode(raw_code: u16) -> Instruction {
+        OpCode::from_u16(raw_code).instruction()
+    }
+
+    pub fn is_skip(&self) -> bool {
+        matches!(
+            self,
+            Instruction::SkipEqualByte(..)
+                | Instruction::SkipNotEqualByte(..)
+                | Instruction::SkipEqual(..)
+                | Instruction::SkipNotEqual(..)
+                | Instruction::SkipKeyPressed(..)
+                | Instruction::SkipKeyNotPressed(..)
+        )
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::Sys(nnn) => write!(f, "SYS 0x{:03X}", nnn),
+            Instruction::Jump(nnn) => write!(f, "JP 0x{:03X}", nnn),
+            Instruction::Call(nnn) => write!(f, "CALL 0x{:03X}", nnn),
+            Instruction::SkipEqualByte(x, kk) => write!(f, "SE V{:X}, 0x{:02X}", x, kk),
+            Instruction::SkipNotEqualByte(x, kk) => write!(f, "SNE V{:X}, 0x{:02X}", x, kk),
+            Instruction::SkipEqual(x, y) => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::LoadByte(x, kk) => write!(f, "LD V{:X}, 0x{:02X}", x, kk),
+            Instruction::AddByte(x, kk) => write!(f, "ADD V{:X}, 0x{:02X}", x, kk),
+            Instruction::Load(x, y) => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::Or(x, y) => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::And(x, y) => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::Xor(x, y) => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::Add(x, y) => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::Sub(x, y) => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::ShiftRight(x, y) => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::SubN(x, y) => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::ShiftLeft(x, y) => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::SkipNotEqual(x, y) => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Instruction::LoadI(nnn) => write!(f, "LD I, 0x{:03X}", nnn),
+            Instruction::JumpV0(nnn) => write!(f, "JP V0, 0x{:03X}", nnn),
+            Instruction::Random(x, kk) => write!(f, "RND V{:X}, 0x{:02X}", x, kk),
+            Instruction::Draw(x, y, n) => write!(f, "DRW V{:X}, V{:X}, {}", x, y, n),
+            Instruction::SkipKeyPressed(x) => write!(f, "SKP V{:X}", x),
+            Instruction::SkipKeyNotPressed(x) => write!(f, "SKNP V{:X}", x),
+            Instruction::LoadDelayTimer(x) => write!(f, "LD V{:X}, DT", x),
+            Instruction::WaitKey(x) => write!(f, "LD V{:X}, K", x),
+            Instruction::SetDelayTimer(x) => write!(f, "LD DT, V{:X}", x),
+            Instruction::SetSoundTimer(x) => write!(f, "LD ST, V{:X}", x),
+            Instruction::AddI(x) => write!(f, "ADD I, V{:X}", x),
+            Instruction::LoadFont(x) => write!(f, "LD F, V{:X}", x),
+            Instruction::StoreBcd(x) => write!(f, "LD B, V{:X}", x),
+            Instruction::StoreRegisters(x) => write!(f, "LD [I], V{:X}", x),
+            Instruction::LoadRegisters(x) => write!(f, "LD V{:X}, [I]", x),
+            Instruction::Unknown(raw) => write!(f, "DW 0x{:04X}", raw),
+        }
+    }
 }
 
 pub struct OpCode {
@@ -53,30 +159,84 @@ impl OpCode {
             n: nibbles.3 as usize,
         }
     }
+
+    pub fn instruction(&self) -> Instruction {
+        let (x, y, n, kk, nnn) = (self.x, self.y, self.n, self.kk, self.nnn);
+        match self.nibbles {
+            (0x0, 0x0, 0xE, 0x0) => Instruction::Cls,
+            (0x0, 0x0, 0xE, 0xE) => Instruction::Ret,
+            (0x0, _, _, _) => Instruction::Sys(nnn),
+            (0x1, _, _, _) => Instruction::Jump(nnn),
+            (0x2, _, _, _) => Instruction::Call(nnn),
+            (0x3, _, _, _) => Instruction::SkipEqualByte(x, kk),
+            (0x4, _, _, _) => Instruction::SkipNotEqualByte(x, kk),
+            (0x5, _, _, 0) => Instruction::SkipEqual(x, y),
+            (0x6, _, _, _) => Instruction::LoadByte(x, kk),
+            (0x7, _, _, _) => Instruction::AddByte(x, kk),
+            (0x8, _, _, 0x0) => Instruction::Load(x, y),
+            (0x8, _, _, 0x1) => Instruction::Or(x, y),
+            (0x8, _, _, 0x2) => Instruction::And(x, y),
+            (0x8, _, _, 0x3) => Instruction::Xor(x, y),
+            (0x8, _, _, 0x4) => Instruction::Add(x, y),
+            (0x8, _, _, 0x5) => Instruction::Sub(x, y),
+            (0x8, _, _, 0x6) => Instruction::ShiftRight(x, y),
+            (0x8, _, _, 0x7) => Instruction::SubN(x, y),
+            (0x8, _, _, 0xE) => Instruction::ShiftLeft(x, y),
+            (0x9, _, _, _) => Instruction::SkipNotEqual(x, y),
+            (0xA, _, _, _) => Instruction::LoadI(nnn),
+            (0xB, _, _, _) => Instruction::JumpV0(nnn),
+            (0xC, _, _, _) => Instruction::Random(x, kk),
+            (0xD, _, _, _) => Instruction::Draw(x, y, n),
+            (0xE, _, 0x9, 0xE) => Instruction::SkipKeyPressed(x),
+            (0xE, _, 0xA, 0x1) => Instruction::SkipKeyNotPressed(x),
+            (0xF, _, 0x0, 0x7) => Instruction::LoadDelayTimer(x),
+            (0xF, _, 0x0, 0xA) => Instruction::WaitKey(x),
+            (0xF, _, 0x1, 0x5) => Instruction::SetDelayTimer(x),
+            (0xF, _, 0x1, 0x8) => Instruction::SetSoundTimer(x),
+            (0xF, _, 0x1, 0xE) => Instruction::AddI(x),
+            (0xF, _, 0x2, 0x9) => Instruction::LoadFont(x),
+            (0xF, _, 0x3, 0x3) => Instruction::StoreBcd(x),
+            (0xF, _, 0x5, 0x5) => Instruction::StoreRegisters(x),
+            (0xF, _, 0x6, 0x5) => Instruction::LoadRegisters(x),
+            (_, _, _, _) => Instruction::Unknown(self.raw),
+        }
+    }
 }
 
 pub struct Cpu {
-    rng: rand::rngs::ThreadRng,
+    rng: Box<dyn RandomSource>,
     pub memory: Vec<u8>,
     pub v: Vec<u8>,
     pub pc: usize,
     pub i: u16,
-    pub graphics: [[u8; 64]; 32],
+    pub graphics: Framebuffer,
     pub delay_timer: u8,
     pub sound_timer: u8,
     pub stack: Vec<usize>,
     pub sp: usize,
     pub key: Vec<bool>,
     pub wait_key: bool,
+    // Set by CLS and DRW, cleared by the frontend once it has drawn.
+    dirty: bool,
 }
 
 impl Cpu {
+    #[cfg(feature = "std")]
+    pub fn initialize() -> Cpu {
+        Cpu::with_random(Box::new(ThreadRandom::new()))
+    }
+
+    #[cfg(not(feature = "std"))]
     pub fn initialize() -> Cpu {
+        Cpu::with_random(Box::new(XorShiftRandom::new(0)))
+    }
+
+    pub fn with_random(rng: Box<dyn RandomSource>) -> Cpu {
         let mut result = Cpu {
-            rng: rand::thread_rng(),
+            rng,
             memory: vec![0; 4096],
             v: vec![0; 16],
-            graphics: [[0; 64]; 32],
+            graphics: Framebuffer::new(),
             pc: 0x200,
             i: 0,
             delay_timer: 0,
@@ -85,6 +245,7 @@ impl Cpu {
             sp: 0,
             key: vec![false; 16],
             wait_key: false,
+            dirty: true,
         };
         result.initialize_font_data();
 
@@ -106,22 +267,42 @@ impl Cpu {
         self.memory[start_address..end_address].copy_from_slice(&font_data);
     }
 
+    #[cfg(feature = "std")]
     pub fn load_rom(&mut self, filepath: &str) {
         let rom = fs::read(filepath).expect("Unable to read file.");
+        self.load_program(&rom);
+    }
+
+    pub fn load_program(&mut self, rom: &[u8]) {
         let start_address: usize = 0x200;
         let end_address = start_address + rom.len();
         if end_address > self.memory.len() {
             panic!("Rom is larger than memory. Aborting.");
         }
 
-        self.memory[start_address..end_address].copy_from_slice(&rom);
+        self.memory[start_address..end_address].copy_from_slice(rom);
     }
 
-    pub fn step(&mut self) -> StepResult {
+    pub fn step(&mut self) -> StepResult<'_> {
         let opcode = (self.memory[self.pc] as u16) << 8 | (self.memory[self.pc + 1] as u16);
         self.cpu_step(opcode)
     }
 
+    // Whether the framebuffer changed since the last `take_dirty`.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn take_dirty(&mut self) -> bool {
+        core::mem::replace(&mut self.dirty, false)
+    }
+
+    fn inst_00e0(&mut self) -> ProgramCounterAction {
+        self.graphics.clear();
+        self.dirty = true;
+        ProgramCounterAction::Advance
+    }
+
     fn inst_00ee(&mut self) -> ProgramCounterAction {
         self.pc = self.stack[self.sp];
         self.sp -= 1;
@@ -178,17 +359,17 @@ impl Cpu {
     }
 
     fn inst_8xy1(&mut self, opcode: OpCode) -> ProgramCounterAction {
-        self.v[opcode.x] = self.v[opcode.x] | self.v[opcode.y];
+        self.v[opcode.x] |= self.v[opcode.y];
         ProgramCounterAction::Advance
     }
 
     fn inst_8xy2(&mut self, opcode: OpCode) -> ProgramCounterAction {
-        self.v[opcode.x] = self.v[opcode.x] & self.v[opcode.y];
+        self.v[opcode.x] &= self.v[opcode.y];
         ProgramCounterAction::Advance
     }
 
     fn inst_8xy3(&mut self, opcode: OpCode) -> ProgramCounterAction {
-        self.v[opcode.x] = self.v[opcode.x] ^ self.v[opcode.y];
+        self.v[opcode.x] ^= self.v[opcode.y];
         ProgramCounterAction::Advance
     }
 
@@ -212,7 +393,7 @@ impl Cpu {
 
     fn inst_8xy6(&mut self, opcode: OpCode) -> ProgramCounterAction {
         self.v[0xF] = if self.v[opcode.x] & 0x1 > 0 { 1 } else { 0 };
-        self.v[opcode.x] = self.v[opcode.x] / 2;
+        self.v[opcode.x] /= 2;
         ProgramCounterAction::Advance
     }
 
@@ -229,7 +410,7 @@ impl Cpu {
 
     fn inst_8xye(&mut self, opcode: OpCode) -> ProgramCounterAction {
         self.v[0xF] = if self.v[opcode.x] & 0x80 > 0 { 1 } else { 0 };
-        self.v[opcode.x] = self.v[opcode.x] << 1;
+        self.v[opcode.x] <<= 1;
         ProgramCounterAction::Advance
     }
 
@@ -250,22 +431,22 @@ impl Cpu {
     }
 
     fn inst_cxkk(&mut self, opcode: OpCode) -> ProgramCounterAction {
-        let random: u8 = self.rng.gen();
+        let random = self.rng.next_byte();
         self.v[opcode.x] = random & opcode.kk;
         ProgramCounterAction::Advance
     }
 
     fn inst_dxyn(&mut self, opcode: OpCode) -> ProgramCounterAction {
         self.v[0xF] = 0;
+        let x = self.v[opcode.x] as usize;
         for byte in 0..opcode.n {
-            let y = (self.v[opcode.y] as usize + byte as usize) % 32;
-            for bit in 0..8 {
-                let x = (self.v[opcode.x] as usize + bit) % 64;
-                let color = (self.memory[self.i as usize + byte as usize] >> (7 - bit)) & 1;
-                self.v[0x0f] |= color & self.graphics[y][x];
-                self.graphics[y][x] ^= color;
+            let y = (self.v[opcode.y] as usize + byte) % HEIGHT;
+            let sprite = self.memory[self.i as usize + byte];
+            if self.graphics.draw_sprite_row(x, y, sprite) {
+                self.v[0x0f] = 1;
             }
         }
+        self.dirty = true;
         ProgramCounterAction::Advance
     }
 
@@ -314,7 +495,7 @@ impl Cpu {
     }
 
     fn inst_fx1e(&mut self, opcode: OpCode) -> ProgramCounterAction {
-        self.i = self.i + self.v[opcode.x] as u16;
+        self.i += self.v[opcode.x] as u16;
         ProgramCounterAction::Advance
     }
 
@@ -345,48 +526,46 @@ impl Cpu {
         ProgramCounterAction::Advance
     }
 
-    pub fn cpu_step(&mut self, opcode: u16) -> StepResult {
+    pub fn cpu_step(&mut self, opcode: u16) -> StepResult<'_> {
         let opcode = OpCode::from_u16(opcode);
-        let pc_action = match opcode.nibbles {
-            (0x0, 0x0, 0xE, 0x0) => ProgramCounterAction::Advance,
-            (0x0, 0x0, 0xE, 0xE) => self.inst_00ee(),
-            (0x0, _, _, _) => ProgramCounterAction::Advance,
-            (0x1, _, _, _) => self.inst_1nnn(opcode),
-            (0x2, _, _, _) => self.inst_2nnn(opcode),
-            (0x3, _, _, _) => self.inst_3xkk(opcode),
-            (0x4, _, _, _) => self.inst_4xkk(opcode),
-            (0x5, _, _, 0) => self.inst_5xy0(opcode),
-            (0x6, _, _, _) => self.inst_6xkk(opcode),
-            (0x7, _, _, _) => self.inst_7xkk(opcode),
-            (0x8, _, _, 0x0) => self.inst_8xy0(opcode),
-            (0x8, _, _, 0x1) => self.inst_8xy1(opcode),
-            (0x8, _, _, 0x2) => self.inst_8xy2(opcode),
-            (0x8, _, _, 0x3) => self.inst_8xy3(opcode),
-            (0x8, _, _, 0x4) => self.inst_8xy4(opcode),
-            (0x8, _, _, 0x5) => self.inst_8xy5(opcode),
-            (0x8, _, _, 0x6) => self.inst_8xy6(opcode),
-            (0x8, _, _, 0x7) => self.inst_8xy7(opcode),
-            (0x8, _, _, 0xE) => self.inst_8xye(opcode),
-            (0x9, _, _, _) => self.inst_9xy0(opcode),
-            (0xA, _, _, _) => self.inst_annn(opcode),
-            (0xB, _, _, _) => self.inst_bnnn(opcode),
-            (0xC, _, _, _) => self.inst_cxkk(opcode),
-            (0xD, _, _, _) => self.inst_dxyn(opcode),
-            (0xE, _, 0x9, 0xE) => self.inst_ex9e(opcode),
-            (0xE, _, 0xA, 0x1) => self.inst_exa1(opcode),
-            (0xF, _, 0x0, 0x7) => self.inst_fx07(opcode),
-            (0xF, _, 0x0, 0xA) => self.inst_fx0a(opcode),
-            (0xF, _, 0x1, 0x5) => self.inst_fx15(opcode),
-            (0xF, _, 0x1, 0x8) => self.inst_fx18(opcode),
-            (0xF, _, 0x1, 0xE) => self.inst_fx1e(opcode),
-            (0xF, _, 0x2, 0x9) => self.inst_fx29(opcode),
-            (0xF, _, 0x3, 0x3) => self.inst_fx33(opcode),
-            (0xF, _, 0x5, 0x5) => self.inst_fx55(opcode),
-            (0xF, _, 0x6, 0x5) => self.inst_fx65(opcode),
-            (_, _, _, _) => {
-                eprintln!("Unknown opcode");
-                ProgramCounterAction::Advance
-            }
+        let instruction = opcode.instruction();
+        let pc_action = match instruction {
+            Instruction::Cls => self.inst_00e0(),
+            Instruction::Ret => self.inst_00ee(),
+            Instruction::Sys(_) => ProgramCounterAction::Advance,
+            Instruction::Jump(_) => self.inst_1nnn(opcode),
+            Instruction::Call(_) => self.inst_2nnn(opcode),
+            Instruction::SkipEqualByte(..) => self.inst_3xkk(opcode),
+            Instruction::SkipNotEqualByte(..) => self.inst_4xkk(opcode),
+            Instruction::SkipEqual(..) => self.inst_5xy0(opcode),
+            Instruction::LoadByte(..) => self.inst_6xkk(opcode),
+            Instruction::AddByte(..) => self.inst_7xkk(opcode),
+            Instruction::Load(..) => self.inst_8xy0(opcode),
+            Instruction::Or(..) => self.inst_8xy1(opcode),
+            Instruction::And(..) => self.inst_8xy2(opcode),
+            Instruction::Xor(..) => self.inst_8xy3(opcode),
+            Instruction::Add(..) => self.inst_8xy4(opcode),
+            Instruction::Sub(..) => self.inst_8xy5(opcode),
+            Instruction::ShiftRight(..) => self.inst_8xy6(opcode),
+            Instruction::SubN(..) => self.inst_8xy7(opcode),
+            Instruction::ShiftLeft(..) => self.inst_8xye(opcode),
+            Instruction::SkipNotEqual(..) => self.inst_9xy0(opcode),
+            Instruction::LoadI(_) => self.inst_annn(opcode),
+            Instruction::JumpV0(_) => self.inst_bnnn(opcode),
+            Instruction::Random(..) => self.inst_cxkk(opcode),
+            Instruction::Draw(..) => self.inst_dxyn(opcode),
+            Instruction::SkipKeyPressed(_) => self.inst_ex9e(opcode),
+            Instruction::SkipKeyNotPressed(_) => self.inst_exa1(opcode),
+            Instruction::LoadDelayTimer(_) => self.inst_fx07(opcode),
+            Instruction::WaitKey(_) => self.inst_fx0a(opcode),
+            Instruction::SetDelayTimer(_) => self.inst_fx15(opcode),
+            Instruction::SetSoundTimer(_) => self.inst_fx18(opcode),
+            Instruction::AddI(_) => self.inst_fx1e(opcode),
+            Instruction::LoadFont(_) => self.inst_fx29(opcode),
+            Instruction::StoreBcd(_) => self.inst_fx33(opcode),
+            Instruction::StoreRegisters(_) => self.inst_fx55(opcode),
+            Instruction::LoadRegisters(_) => self.inst_fx65(opcode),
+            Instruction::Unknown(_) => ProgramCounterAction::Advance,
         };
 
         if self.delay_timer > 0 {
@@ -404,65 +583,20 @@ impl Cpu {
         }
 
         StepResult {
-            graphics: self.graphics,
+            graphics: &self.graphics,
             make_sound: self.sound_timer != 0,
+            unknown_opcode: match instruction {
+                Instruction::Unknown(raw) => Some(raw),
+                _ => None,
+            },
         }
     }
-    pub fn check_key_events(&mut self, sdl_context: &sdl2::Sdl) -> bool {
-        let mut event_pump = sdl_context
-            .event_pump()
-            .expect("Unable to poll events from sdl");
-        for event in event_pump.poll_iter() {
-            let key_state = match event {
-                Event::KeyDown { .. } => true,
-                _ => false,
-            };
-
-            let action = match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => KeyActions::Quit,
-                Event::KeyDown {
-                    keycode: Some(code),
-                    ..
-                }
-                | Event::KeyUp {
-                    keycode: Some(code),
-                    ..
-                } => match code {
-                    Keycode::Q => KeyActions::KeyUpDown(0xa),
-                    Keycode::W => KeyActions::KeyUpDown(0xb),
-                    Keycode::E => KeyActions::KeyUpDown(0xc),
-                    Keycode::R => KeyActions::KeyUpDown(0xd),
-                    Keycode::T => KeyActions::KeyUpDown(0xe),
-                    Keycode::Y => KeyActions::KeyUpDown(0xf),
-                    Keycode::U => KeyActions::KeyUpDown(0),
-                    Keycode::I => KeyActions::KeyUpDown(1),
-                    Keycode::A => KeyActions::KeyUpDown(2),
-                    Keycode::S => KeyActions::KeyUpDown(3),
-                    Keycode::D => KeyActions::KeyUpDown(4),
-                    Keycode::F => KeyActions::KeyUpDown(5),
-                    Keycode::G => KeyActions::KeyUpDown(6),
-                    Keycode::H => KeyActions::KeyUpDown(7),
-                    Keycode::J => KeyActions::KeyUpDown(8),
-                    Keycode::K => KeyActions::KeyUpDown(9),
-                    _ => KeyActions::None,
-                },
-                _ => KeyActions::None,
-            };
-
-            match action {
-                KeyActions::Quit => {
-                    return true;
-                }
-                KeyActions::KeyUpDown(address) => {
-                    self.key[address] = key_state;
-                }
-                KeyActions::None => {}
-            }
-        }
-        return false;
+
+    pub fn set_key(&mut self, key: usize, pressed: bool) {
+        self.key[key] = pressed;
+    }
+
+    pub fn is_key_pressed(&self, key: usize) -> bool {
+        self.key[key]
     }
 }
@@ -0,0 +1,211 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Retro looks applied to an RGBA8 image after rasterizing. Strengths are
+// percentages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtEffect {
+    Scanlines,
+    Grid,
+    Bloom,
+    Vignette,
+}
+
+pub const EFFECTS: [CrtEffect; 4] = [
+    CrtEffect::Scanlines,
+    CrtEffect::Grid,
+    CrtEffect::Bloom,
+    CrtEffect::Vignette,
+];
+
+impl CrtEffect {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrtEffect::Scanlines => "scanlines",
+            CrtEffect::Grid => "grid",
+            CrtEffect::Bloom => "bloom",
+            CrtEffect::Vignette => "vignette",
+        }
+    }
+
+    pub fn default_strength(&self) -> u8 {
+        match self {
+            CrtEffect::Scanlines => 40,
+            CrtEffect::Grid => 50,
+            CrtEffect::Bloom => 40,
+            CrtEffect::Vignette => 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CrtFilters {
+    enabled: [bool; 4],
+    // Zero means the default strength of the effect.
+    strengths: [u8; 4],
+}
+
+impl CrtFilters {
+    // Parses a comma separated list such as `scanlines:30,bloom`.
+    pub fn parse(text: &str) -> Result<CrtFilters, String> {
+        let mut filters = CrtFilters::default();
+        for item in text
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let (name, strength) = match item.split_once(':') {
+                Some((name, strength)) => match strength.parse::<u8>() {
+                    Ok(strength) if strength <= 100 => (name, Some(strength)),
+                    _ => return Err(format!("Strength `{}` is not a percentage.", strength)),
+                },
+                None => (item, None),
+            };
+            let effect = EFFECTS
+                .iter()
+                .find(|effect| effect.name() == name)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown CRT effect `{}`. Use scanlines, grid, bloom or vignette.",
+                        name
+                    )
+                })?;
+            filters.set(*effect, strength.unwrap_or(effect.default_strength()));
+        }
+        Ok(filters)
+    }
+
+    pub fn set(&mut self, effect: CrtEffect, strength: u8) {
+        self.enabled[effect as usize] = true;
+        self.strengths[effect as usize] = strength.min(100);
+    }
+
+    pub fn is_enabled(&self, effect: CrtEffect) -> bool {
+        self.enabled[effect as usize]
+    }
+
+    // The configured strength, also while the effect is switched off.
+    pub fn strength(&self, effect: CrtEffect) -> u8 {
+        match self.strengths[effect as usize] {
+            0 => effect.default_strength(),
+            strength => strength,
+        }
+    }
+
+    // Returns whether the effect is now enabled.
+    pub fn toggle(&mut self, effect: CrtEffect) -> bool {
+        self.enabled[effect as usize] = !self.enabled[effect as usize];
+        self.enabled[effect as usize]
+    }
+
+    pub fn any(&self) -> bool {
+        self.enabled.iter().any(|enabled| *enabled)
+    }
+
+    fn active(&self, effect: CrtEffect) -> Option<u32> {
+        Some(self.strength(effect) as u32).filter(|_| self.is_enabled(effect))
+    }
+
+    // Applies the enabled effects to an RGBA8 image that shows the screen at
+    // `scale` image pixels per CHIP-8 pixel.
+    pub fn apply(&self, buffer: &mut [u8], width: usize, height: usize, scale: usize) {
+        assert_eq!(
+            buffer.len(),
+            width * height * 4,
+            "Buffer has the wrong size"
+        );
+        if let Some(strength) = self.active(CrtEffect::Grid).filter(|_| scale >= 3) {
+            for_each_pixel(buffer, width, |x, y, pixel| {
+                if x % scale == scale - 1 || y % scale == scale - 1 {
+                    darken(pixel, strength);
+                }
+            });
+        }
+        if let Some(strength) = self.active(CrtEffect::Scanlines).filter(|_| scale >= 2) {
+            for_each_pixel(buffer, width, |_, y, pixel| {
+                if y % 2 == 1 {
+                    darken(pixel, strength);
+                }
+            });
+        }
+        if let Some(strength) = self.active(CrtEffect::Bloom) {
+            bloom(buffer, width, height, (scale / 2).max(1), strength);
+        }
+        if let Some(strength) = self.active(CrtEffect::Vignette) {
+            // Distances from the centre in 1/1024ths of the half width or
+            // height, so the corners are darkened by `strength` percent.
+            let (width, height) = (width as i64, height as i64);
+            for_each_pixel(buffer, width as usize, |x, y, pixel| {
+                let dx = (2 * x as i64 + 1 - width) * 1024 / width;
+                let dy = (2 * y as i64 + 1 - height) * 1024 / height;
+                let distance = ((dx * dx + dy * dy) >> 10) as u32;
+                darken(pixel, strength * distance / 2048);
+            });
+        }
+    }
+}
+
+fn for_each_pixel<F>(buffer: &mut [u8], width: usize, mut f: F)
+where
+    F: FnMut(usize, usize, &mut [u8]),
+{
+    for (index, pixel) in buffer.chunks_exact_mut(4).enumerate() {
+        f(index % width, index / width, pixel);
+    }
+}
+
+fn darken(pixel: &mut [u8], percent: u32) {
+    for channel in &mut pixel[..3] {
+        *channel = (*channel as u32 * (100 - percent.min(100)) / 100) as u8;
+    }
+}
+
+// Adds a box-blurred copy of the image on top of itself, so lit pixels glow
+// into their dark neighbours.
+fn bloom(buffer: &mut [u8], width: usize, height: usize, radius: usize, strength: u32) {
+    let source: Vec<u32> = buffer.iter().map(|channel| *channel as u32).collect();
+    let mut horizontal = vec![0; source.len()];
+    let mut blurred = vec![0; source.len()];
+    for channel in 0..3 {
+        for y in 0..height {
+            let start = y * width * 4 + channel;
+            box_sum(&source, &mut horizontal, start, 4, width, radius);
+        }
+        for x in 0..width {
+            let start = x * 4 + channel;
+            box_sum(&horizontal, &mut blurred, start, width * 4, height, radius);
+        }
+    }
+    let window = (2 * radius + 1) as u32;
+    for (index, channel) in buffer.iter_mut().enumerate() {
+        if index % 4 != 3 {
+            let glow = blurred[index] / (window * window) * strength / 100;
+            *channel = (*channel as u32 + glow).min(255) as u8;
+        }
+    }
+}
+
+// Sums the `2 * radius + 1` values around each of the `length` values that
+// are `step` apart from `start`.
+fn box_sum(
+    source: &[u32],
+    target: &mut [u32],
+    start: usize,
+    step: usize,
+    length: usize,
+    radius: usize,
+) {
+    let at = |index: usize| source[start + index * step];
+    let mut sum: u32 = (0..=radius.min(length - 1)).map(at).sum();
+    for index in 0..length {
+        target[start + index * step] = sum;
+        if index + radius + 1 < length {
+            sum += at(index + radius + 1);
+        }
+        if index >= radius {
+            sum -= at(index - radius);
+        }
+    }
+}
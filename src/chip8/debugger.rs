@@ -0,0 +1,206 @@
+use super::cheats::CheatEngine;
+use super::cpu::Cpu;
+use super::expression::{Expression, ParseError};
+use std::io::{self, BufRead, Write};
+
+pub struct Breakpoint {
+    pub source: String,
+    pub condition: Expression,
+}
+
+pub struct Watchpoint {
+    pub source: String,
+    pub expression: Expression,
+    last_value: Option<i64>,
+}
+
+pub struct TraceFilter {
+    pub source: String,
+    pub condition: Expression,
+}
+
+pub enum DebugEvent {
+    Breakpoint(usize),
+    Watchpoint { index: usize, old: i64, new: i64 },
+    Trace(String),
+    Step,
+}
+
+impl DebugEvent {
+    pub fn stops_execution(&self) -> bool {
+        !matches!(self, DebugEvent::Trace(_))
+    }
+}
+
+pub enum DebuggerCommand {
+    Continue,
+    Quit,
+}
+
+#[derive(Default)]
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    pub watchpoints: Vec<Watchpoint>,
+    pub trace_filters: Vec<TraceFilter>,
+    single_step: bool,
+}
+
+pub fn format_state(cpu: &Cpu) -> String {
+    let registers: Vec<String> = cpu.v.iter().map(|value| format!("{:02X}", value)).collect();
+    format!(
+        "pc={:03X} i={:03X} sp={:X} dt={:02X} st={:02X} v=[{}]",
+        cpu.pc,
+        cpu.i,
+        cpu.sp,
+        cpu.delay_timer,
+        cpu.sound_timer,
+        registers.join(" ")
+    )
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        Debugger::default()
+    }
+
+    pub fn add_breakpoint(&mut self, source: &str) -> Result<usize, ParseError> {
+        self.breakpoints.push(Breakpoint {
+            source: source.to_string(),
+            condition: Expression::parse(source)?,
+        });
+        Ok(self.breakpoints.len() - 1)
+    }
+
+    pub fn add_watchpoint(&mut self, source: &str) -> Result<usize, ParseError> {
+        self.watchpoints.push(Watchpoint {
+            source: source.to_string(),
+            expression: Expression::parse(source)?,
+            last_value: None,
+        });
+        Ok(self.watchpoints.len() - 1)
+    }
+
+    pub fn add_trace_filter(&mut self, source: &str) -> Result<usize, ParseError> {
+        self.trace_filters.push(TraceFilter {
+            source: source.to_string(),
+            condition: Expression::parse(source)?,
+        });
+        Ok(self.trace_filters.len() - 1)
+    }
+
+    pub fn request_break(&mut self) {
+        self.single_step = true;
+    }
+
+    pub fn after_step(&mut self, cpu: &Cpu) -> Vec<DebugEvent> {
+        let mut events = Vec::new();
+
+        for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+            if breakpoint.condition.is_true(cpu) {
+                events.push(DebugEvent::Breakpoint(index));
+            }
+        }
+
+        for (index, watchpoint) in self.watchpoints.iter_mut().enumerate() {
+            let new = watchpoint.expression.evaluate(cpu);
+            if let Some(old) = watchpoint.last_value {
+                if old != new {
+                    events.push(DebugEvent::Watchpoint { index, old, new });
+                }
+            }
+            watchpoint.last_value = Some(new);
+        }
+
+        if self
+            .trace_filters
+            .iter()
+            .any(|filter| filter.condition.is_true(cpu))
+        {
+            events.push(DebugEvent::Trace(format_state(cpu)));
+        }
+
+        if self.single_step {
+            self.single_step = false;
+            events.push(DebugEvent::Step);
+        }
+
+        events
+    }
+
+    pub fn describe(&self, event: &DebugEvent) -> String {
+        match event {
+            DebugEvent::Breakpoint(index) => format!(
+                "Breakpoint {} hit: {}",
+                index, self.breakpoints[*index].source
+            ),
+            DebugEvent::Watchpoint { index, old, new } => format!(
+                "Watchpoint {} changed: {} ({} -> {})",
+                index, self.watchpoints[*index].source, old, new
+            ),
+            DebugEvent::Trace(state) => format!("trace: {}", state),
+            DebugEvent::Step => String::from("Stopped"),
+        }
+    }
+
+    // Runs a small command prompt on stdin while the emulation is stopped.
+    pub fn interact(&mut self, cpu: &mut Cpu, cheats: &mut CheatEngine) -> DebuggerCommand {
+        println!("{}", format_state(cpu));
+        let stdin = io::stdin();
+        loop {
+            print!("(chip8) ");
+            io::stdout().flush().expect("Unable to flush stdout.");
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+                return DebuggerCommand::Quit;
+            }
+            let line = line.trim();
+            let (command, argument) = match line.find(' ') {
+                Some(index) => (&line[..index], line[index + 1..].trim()),
+                None => (line, ""),
+            };
+
+            match command {
+                "" | "c" | "continue" => return DebuggerCommand::Continue,
+                "s" | "step" => {
+                    self.single_step = true;
+                    return DebuggerCommand::Continue;
+                }
+                "q" | "quit" => return DebuggerCommand::Quit,
+                "r" | "regs" => println!("{}", format_state(cpu)),
+                "p" | "print" => match Expression::parse(argument) {
+                    Ok(expression) => {
+                        let value = expression.evaluate(cpu);
+                        println!("{} = {} (0x{:X})", argument, value, value)
+                    }
+                    Err(error) => println!("{}", error),
+                },
+                "b" | "break" => report(self.add_breakpoint(argument), "Breakpoint"),
+                "w" | "watch" => report(self.add_watchpoint(argument), "Watchpoint"),
+                "t" | "trace" => report(self.add_trace_filter(argument), "Trace filter"),
+                "d" | "delete" => {
+                    self.breakpoints.clear();
+                    self.watchpoints.clear();
+                    self.trace_filters.clear();
+                    println!("Deleted all breakpoints, watchpoints and trace filters.");
+                }
+                _ => match cheats.execute(command, argument, cpu) {
+                    Some(output) => println!("{}", output),
+                    None => println!(
+                        "Commands: c(ontinue), s(tep), q(uit), r(egs), p(rint) EXPR, \
+                         b(reak) EXPR, w(atch) EXPR, t(race) EXPR, d(elete), snapshot, \
+                         search eq N|inc|dec|same|changed, freeze LOC VALUE, unfreeze LOC, \
+                         cheats, save"
+                    ),
+                },
+            }
+        }
+    }
+}
+
+fn report(result: Result<usize, ParseError>, kind: &str) {
+    match result {
+        Ok(index) => println!("{} {} added.", kind, index),
+        Err(error) => println!("{}", error),
+    }
+}
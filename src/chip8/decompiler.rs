@@ -0,0 +1,380 @@
+use super::analysis::{ControlFlowGraph, EdgeKind, PROGRAM_START};
+use super::cpu::Instruction;
+use std::collections::BTreeSet;
+use std::fmt;
+
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Compare(String, bool, String),
+    Key(usize, bool),
+}
+
+impl Condition {
+    // The condition under which a skip instruction skips the next instruction.
+    fn of_skip(instruction: Instruction) -> Option<Condition> {
+        let register = |x| format!("v{:x}", x);
+        match instruction {
+            Instruction::SkipEqualByte(x, kk) => {
+                Some(Condition::Compare(register(x), true, kk.to_string()))
+            }
+            Instruction::SkipNotEqualByte(x, kk) => {
+                Some(Condition::Compare(register(x), false, kk.to_string()))
+            }
+            Instruction::SkipEqual(x, y) => {
+                Some(Condition::Compare(register(x), true, register(y)))
+            }
+            Instruction::SkipNotEqual(x, y) => {
+                Some(Condition::Compare(register(x), false, register(y)))
+            }
+            Instruction::SkipKeyPressed(x) => Some(Condition::Key(x, true)),
+            Instruction::SkipKeyNotPressed(x) => Some(Condition::Key(x, false)),
+            _ => None,
+        }
+    }
+
+    fn negate(&self) -> Condition {
+        match self {
+            Condition::Compare(lhs, equal, rhs) => {
+                Condition::Compare(lhs.clone(), !equal, rhs.clone())
+            }
+            Condition::Key(x, pressed) => Condition::Key(*x, !pressed),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Compare(lhs, true, rhs) => write!(f, "{} == {}", lhs, rhs),
+            Condition::Compare(lhs, false, rhs) => write!(f, "{} != {}", lhs, rhs),
+            Condition::Key(x, true) => write!(f, "key(v{:x})", x),
+            Condition::Key(x, false) => write!(f, "!key(v{:x})", x),
+        }
+    }
+}
+
+fn function_name(address: usize) -> String {
+    if address == PROGRAM_START {
+        String::from("main")
+    } else {
+        format!("sub_{:03X}", address)
+    }
+}
+
+fn statement(instruction: Instruction) -> String {
+    let v = |x| format!("v{:x}", x);
+    match instruction {
+        Instruction::Cls => String::from("clear();"),
+        Instruction::Ret => String::from("return;"),
+        Instruction::Sys(nnn) => format!("sys(0x{:03X});", nnn),
+        Instruction::Jump(nnn) => format!("goto L_{:03X};", nnn),
+        Instruction::Call(nnn) => format!("{}();", function_name(nnn)),
+        Instruction::LoadByte(x, kk) => format!("{} = {};", v(x), kk),
+        Instruction::AddByte(x, kk) if kk >= 0x80 => format!("{} -= {};", v(x), 256 - kk as u16),
+        Instruction::AddByte(x, kk) => format!("{} += {};", v(x), kk),
+        Instruction::Load(x, y) => format!("{} = {};", v(x), v(y)),
+        Instruction::Or(x, y) => format!("{} |= {};", v(x), v(y)),
+        Instruction::And(x, y) => format!("{} &= {};", v(x), v(y)),
+        Instruction::Xor(x, y) => format!("{} ^= {};", v(x), v(y)),
+        Instruction::Add(x, y) => format!("{} += {}; // vf = carry", v(x), v(y)),
+        Instruction::Sub(x, y) => format!("{} -= {}; // vf = no borrow", v(x), v(y)),
+        Instruction::ShiftRight(x, _) => format!("{} >>= 1; // vf = shifted out bit", v(x)),
+        Instruction::SubN(x, y) => format!("{} = {} - {}; // vf = no borrow", v(x), v(y), v(x)),
+        Instruction::ShiftLeft(x, _) => format!("{} <<= 1; // vf = shifted out bit", v(x)),
+        Instruction::LoadI(nnn) => format!("i = 0x{:03X};", nnn),
+        Instruction::JumpV0(nnn) => format!("jump(0x{:03X} + v0);", nnn),
+        Instruction::Random(x, kk) => format!("{} = random() & 0x{:02X};", v(x), kk),
+        Instruction::Draw(x, y, n) => format!("vf = draw({}, {}, {});", v(x), v(y), n),
+        Instruction::LoadDelayTimer(x) => format!("{} = delay;", v(x)),
+        Instruction::WaitKey(x) => format!("{} = wait_key();", v(x)),
+        Instruction::SetDelayTimer(x) => format!("delay = {};", v(x)),
+        Instruction::SetSoundTimer(x) => format!("sound = {};", v(x)),
+        Instruction::AddI(x) => format!("i += {};", v(x)),
+        Instruction::LoadFont(x) => format!("i = font({});", v(x)),
+        Instruction::StoreBcd(x) => format!("mem[i..i + 3] = bcd({});", v(x)),
+        Instruction::StoreRegisters(x) => format!("mem[i..i + {}] = v0..{};", x + 1, v(x)),
+        Instruction::LoadRegisters(x) => format!("v0..{} = mem[i..i + {}];", v(x), x + 1),
+        Instruction::Unknown(raw) => format!("// unknown opcode 0x{:04X}", raw),
+        skip => format!("// {}", skip),
+    }
+}
+
+struct Line {
+    address: Option<usize>,
+    depth: usize,
+    text: String,
+}
+
+struct LoopContext {
+    header: usize,
+    exit: usize,
+}
+
+// Structures one function at a time. Loops come from backward jumps, if/else
+// from a skip followed by a forward jump; anything else falls back to goto.
+struct FunctionWriter<'a> {
+    graph: &'a ControlFlowGraph,
+    body: BTreeSet<usize>,
+    lines: Vec<Line>,
+    labels: BTreeSet<usize>,
+    loops: Vec<LoopContext>,
+    jump_targets: BTreeSet<usize>,
+}
+
+impl<'a> FunctionWriter<'a> {
+    fn new(graph: &'a ControlFlowGraph, entry: usize) -> FunctionWriter<'a> {
+        FunctionWriter {
+            graph,
+            body: graph.function_body(entry),
+            lines: Vec::new(),
+            labels: BTreeSet::new(),
+            loops: Vec::new(),
+            jump_targets: graph
+                .edges
+                .values()
+                .flatten()
+                .filter(|edge| edge.kind == EdgeKind::Jump)
+                .map(|edge| edge.target)
+                .collect(),
+        }
+    }
+
+    fn instruction(&self, address: usize) -> Option<Instruction> {
+        if self.body.contains(&address) {
+            Some(self.graph.instructions[&address])
+        } else {
+            None
+        }
+    }
+
+    fn emit(&mut self, address: Option<usize>, depth: usize, text: String) {
+        self.lines.push(Line {
+            address,
+            depth,
+            text,
+        });
+    }
+
+    fn jump(&mut self, target: usize) -> String {
+        for context in self.loops.iter().rev() {
+            if target == context.exit {
+                return String::from("break;");
+            }
+            if target == context.header {
+                return String::from("continue;");
+            }
+        }
+        self.labels.insert(target);
+        format!("goto L_{:03X};", target)
+    }
+
+    // Simple statement at `address`, with jumps resolved against enclosing loops.
+    fn simple(&mut self, address: usize) -> String {
+        match self.graph.instructions[&address] {
+            Instruction::Jump(target) if target == address => String::from("halt();"),
+            Instruction::Jump(target) => self.jump(target),
+            instruction => statement(instruction),
+        }
+    }
+
+    // Instructions that get folded into a structured statement must not be
+    // jumped to, otherwise their label would have nowhere to go.
+    fn is_jump_target(&self, address: usize) -> bool {
+        self.jump_targets.contains(&address)
+    }
+
+    fn loop_end(&self, header: usize, end: usize) -> Option<usize> {
+        self.body
+            .range(header..end)
+            .rev()
+            .find(|address| self.graph.instructions[address] == Instruction::Jump(header))
+            .cloned()
+            .filter(|jump| *jump > header && !self.is_jump_target(*jump))
+    }
+
+    fn write_range(&mut self, start: usize, end: usize, depth: usize) {
+        let mut address = start;
+        while address < end {
+            address = match self.body.range(address..end).next() {
+                Some(next) => self.write_statement(*next, end, depth),
+                None => return,
+            };
+        }
+    }
+
+    // Writes the statement starting at `address` and returns the address after it.
+    fn write_statement(&mut self, address: usize, end: usize, depth: usize) -> usize {
+        if let Some(jump) = self.loop_end(address, end) {
+            self.write_loop(address, jump, depth);
+            return jump + 2;
+        }
+
+        let instruction = self.graph.instructions[&address];
+        if let Some(condition) = Condition::of_skip(instruction) {
+            return self.write_skip(address, condition, end, depth);
+        }
+
+        let text = self.simple(address);
+        self.emit(Some(address), depth, text);
+        address + 2
+    }
+
+    fn write_loop(&mut self, header: usize, jump: usize, depth: usize) {
+        self.loops.push(LoopContext {
+            header,
+            exit: jump + 2,
+        });
+
+        let before_jump = jump - 2;
+        let guard = self
+            .instruction(before_jump)
+            .and_then(Condition::of_skip)
+            .filter(|_| !self.is_jump_target(before_jump));
+        let entry_guard = self
+            .instruction(header)
+            .and_then(Condition::of_skip)
+            .filter(|_| self.instruction(header + 2) == Some(Instruction::Jump(jump + 2)))
+            .filter(|_| !self.is_jump_target(header + 2));
+
+        if let Some(condition) = entry_guard {
+            // SKIP cond; JP exit; ...; JP header
+            self.emit(Some(header), depth, format!("while ({}) {{", condition));
+            self.write_range(header + 4, jump, depth + 1);
+            self.emit(None, depth, String::from("}"));
+        } else if let Some(condition) = guard.filter(|_| before_jump > header) {
+            // ...; SKIP cond; JP header
+            self.emit(Some(header), depth, String::from("do {"));
+            self.write_range(header, before_jump, depth + 1);
+            self.emit(None, depth, format!("}} while ({});", condition.negate()));
+        } else {
+            self.emit(Some(header), depth, String::from("loop {"));
+            self.write_range(header, jump, depth + 1);
+            self.emit(None, depth, String::from("}"));
+        }
+
+        self.loops.pop();
+    }
+
+    fn write_skip(
+        &mut self,
+        address: usize,
+        condition: Condition,
+        end: usize,
+        depth: usize,
+    ) -> usize {
+        let next = address + 2;
+        let after = address + 4;
+
+        if let Some(Instruction::Jump(target)) = self.instruction(next) {
+            if target > after
+                && target <= end
+                && !self.is_jump_target(next)
+                && self.loops.iter().all(|l| l.exit != target)
+            {
+                // SKIP cond; JP else; then...; [JP endif]; else...
+                let else_jump = match self.instruction(target - 2) {
+                    Some(Instruction::Jump(endif))
+                        if endif > target
+                            && endif <= end
+                            && target - 2 >= after
+                            && !self.is_jump_target(target - 2)
+                            && !self.instruction(target - 4).is_some_and(|i| i.is_skip()) =>
+                    {
+                        Some(endif)
+                    }
+                    _ => None,
+                };
+
+                self.emit(Some(address), depth, format!("if ({}) {{", condition));
+                match else_jump {
+                    Some(endif) => {
+                        self.write_range(after, target - 2, depth + 1);
+                        self.emit(None, depth, String::from("} else {"));
+                        self.write_range(target, endif, depth + 1);
+                        self.emit(None, depth, String::from("}"));
+                        return endif;
+                    }
+                    None => {
+                        self.write_range(after, target, depth + 1);
+                        self.emit(None, depth, String::from("}"));
+                        return target;
+                    }
+                }
+            }
+        }
+
+        // SKIP cond; single statement
+        self.emit(
+            Some(address),
+            depth,
+            format!("if ({}) {{", condition.negate()),
+        );
+        match self.instruction(next).and_then(Condition::of_skip) {
+            Some(nested) => {
+                // A skip that skips a skip: fall back to a jump past both.
+                let text = format!("if ({}) {{ {} }}", nested, self.jump(after + 2));
+                self.emit(Some(next), depth + 1, text);
+            }
+            None if self.body.contains(&next) => {
+                let text = self.simple(next);
+                self.emit(Some(next), depth + 1, text);
+            }
+            None => {}
+        }
+        self.emit(None, depth, String::from("}"));
+        after
+    }
+
+    fn finish(mut self, entry: usize) -> String {
+        let end = self.body.iter().next_back().map_or(entry, |last| last + 2);
+        let first = self.body.iter().next().cloned().unwrap_or(entry);
+        if first < entry {
+            // Code reached by jumps back before the entry point.
+            self.labels.insert(entry);
+        }
+        self.write_range(first, end, 1);
+
+        let mut output = format!("fn {}() {{\n", function_name(entry));
+        if first < entry {
+            output += &format!("{}goto L_{:03X};\n", INDENT, entry);
+        }
+        let mut labelled = BTreeSet::new();
+        for line in &self.lines {
+            if let Some(address) = line.address {
+                if self.labels.contains(&address) && labelled.insert(address) {
+                    output += &format!("L_{:03X}:\n", address);
+                }
+            }
+            output += &format!("{}{}\n", INDENT.repeat(line.depth), line.text);
+        }
+        for label in self.labels.difference(&labelled) {
+            output += &format!(
+                "{}// L_{:03X} lies inside a structured statement or another function\n",
+                INDENT, label
+            );
+        }
+        output += "}\n";
+        output
+    }
+}
+
+pub fn decompile(graph: &ControlFlowGraph) -> String {
+    let functions = graph
+        .subroutines
+        .iter()
+        .chain([PROGRAM_START].iter())
+        .filter(|entry| graph.instructions.contains_key(entry))
+        .cloned()
+        .collect::<BTreeSet<usize>>();
+
+    let mut output = String::new();
+    for entry in &functions {
+        if !output.is_empty() {
+            output += "\n";
+        }
+        output += &FunctionWriter::new(graph, *entry).finish(*entry);
+    }
+    output
+}
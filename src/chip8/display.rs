@@ -1,62 +1,326 @@
-const SCALING_FACTOR: u32 = 8;
+use super::crt::CrtFilters;
+use super::flicker::{Deflicker, FlickerFilter};
+use super::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use super::frontend::VideoSink;
+use super::keypad::{self, KeypadPanel};
+use super::osd::{self, Osd, LINE_HEIGHT};
+use super::raster::{letterbox, Palette, Rasterizer, Rgba};
 
-use sdl2::pixels::Color;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
 use sdl2::render::WindowCanvas;
+use sdl2::video::FullscreenType;
 
 pub struct Display {
     canvas: WindowCanvas,
+    palette: Palette,
+    deflicker: Deflicker,
+    crt: CrtFilters,
+    osd: Osd,
+    // Window pixels per CHIP-8 pixel when the window opened.
+    scale: u32,
+    keypad: bool,
+    keys: [bool; 16],
+    graphics: Framebuffer,
+    // Set when the screen, the palette or the window size changed.
+    needs_draw: bool,
+    drawn_size: (u32, u32),
+}
+
+fn color(rgba: Rgba) -> Color {
+    Color::RGBA(rgba[0], rgba[1], rgba[2], rgba[3])
 }
 
 impl Display {
-    pub fn new(sdl_context: &sdl2::Sdl) -> Display {
-        let canvas = Display::setup_display(sdl_context).expect("Unable to set up canvas");
-        Display { canvas: canvas }
+    // Opens a resizable window of `scale` window pixels per CHIP-8 pixel.
+    pub fn new(sdl_context: &sdl2::Sdl, title: &str, scale: u32, palette: Palette) -> Display {
+        assert!(scale > 0, "the scale must be at least 1");
+        let canvas = Display::setup_display(sdl_context, title, scale, palette)
+            .expect("Unable to set up canvas");
+        Display {
+            canvas,
+            palette,
+            deflicker: Deflicker::new(FlickerFilter::Off),
+            crt: CrtFilters::default(),
+            osd: Osd::new("CHIP-8"),
+            scale,
+            keypad: false,
+            keys: [false; 16],
+            graphics: Framebuffer::new(),
+            needs_draw: true,
+            drawn_size: (0, 0),
+        }
+    }
+
+    // Shows the clickable keypad to the right of the screen and widens the
+    // window to make room for it.
+    pub fn show_keypad(&mut self) {
+        self.keypad = true;
+        let width = (WIDTH + keypad::PANEL_SIZE) as u32 * self.scale;
+        let height = HEIGHT as u32 * self.scale;
+        if let Err(error) = self.canvas.window_mut().set_size(width, height) {
+            eprintln!("Unable to resize the window: {}", error);
+        }
+        self.needs_draw = true;
+    }
+
+    pub fn window_size(&self) -> (u32, u32) {
+        self.canvas.window().size()
     }
 
-    fn setup_display(sdl_context: &sdl2::Sdl) -> Result<WindowCanvas, String> {
+    pub fn set_flicker_filter(&mut self, filter: FlickerFilter) {
+        self.deflicker = Deflicker::new(filter);
+    }
+
+    fn setup_display(
+        sdl_context: &sdl2::Sdl,
+        title: &str,
+        scale: u32,
+        palette: Palette,
+    ) -> Result<WindowCanvas, String> {
         let video_subsystem = sdl_context.video()?;
 
         let window = video_subsystem
-            .window(
-                "rust-sdl2 demo: Video",
-                64 * SCALING_FACTOR,
-                32 * SCALING_FACTOR,
-            )
+            .window(title, WIDTH as u32 * scale, HEIGHT as u32 * scale)
             .position_centered()
+            .resizable()
             .opengl()
             .build()
             .map_err(|e| e.to_string())?;
 
         let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
 
-        canvas.set_draw_color(Color::RGB(255, 0, 0));
+        canvas.set_draw_color(color(palette.background()));
         canvas.clear();
         canvas.present();
         Ok(canvas)
     }
 
-    pub fn draw(&mut self, pixels: [[u8; 64]; 32]) {
-        self.canvas.clear();
+    // The colour of a lit pixel after the flicker filter, or `None` for the
+    // background.
+    fn pixel_color(&self, x: usize, y: usize) -> Option<Rgba> {
+        let (value, level) = match self.deflicker.filter {
+            FlickerFilter::Off => (self.graphics.pixel(x, y), 255),
+            _ => self.deflicker.pixel(x, y),
+        };
+        if value != 0 && level != 0 {
+            Some(self.palette.mix(value, level))
+        } else {
+            None
+        }
+    }
+
+    // Draws when the screen, the settings or the window size changed since
+    // the last draw.
+    fn draw_changes(&mut self) {
+        let size = self
+            .canvas
+            .output_size()
+            .expect("Unable to read window size");
+        if self.needs_draw || size != self.drawn_size {
+            self.draw(size.0, size.1);
+            self.needs_draw = false;
+            self.drawn_size = size;
+        }
+    }
+
+    // Draws at the largest integer scale that fits the window, centred, and
+    // leaves the borders black.
+    fn draw(&mut self, width: u32, height: u32) {
+        let (viewport, panel) = if self.keypad {
+            let (viewport, panel) = keypad::layout(width as usize, height as usize);
+            (viewport, Some(panel))
+        } else {
+            (letterbox(width as usize, height as usize), None)
+        };
+        let scale = viewport.scale as u32;
+        let screen = Rect::new(
+            viewport.x as i32,
+            viewport.y as i32,
+            WIDTH as u32 * scale,
+            HEIGHT as u32 * scale,
+        );
         self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.canvas.clear();
+        if self.crt.any() {
+            self.draw_filtered(screen, viewport.scale)
+                .expect("Unable to draw");
+        } else {
+            self.draw_pixels(screen, viewport.scale);
+        }
+        self.draw_osd(screen, viewport.scale);
+        if let Some(panel) = panel {
+            self.draw_keypad(panel, viewport.scale);
+        }
+        self.canvas.present();
+    }
+
+    // Draws the keys in the background colour with their digit in the
+    // foreground colour, and the other way round while they are held.
+    fn draw_keypad(&mut self, panel: KeypadPanel, scale: usize) {
+        let margin = scale.max(2) / 2;
+        for key in 0..16 {
+            let (x, y) = panel.key_position(key);
+            let (fill, text) = if self.keys[key] {
+                (self.palette.foreground(), self.palette.background())
+            } else {
+                (self.palette.background(), self.palette.foreground())
+            };
+            self.canvas.set_draw_color(color(fill));
+            self.canvas
+                .fill_rect(Rect::new(
+                    (x + margin) as i32,
+                    (y + margin) as i32,
+                    (panel.cell - 2 * margin) as u32,
+                    (panel.cell - 2 * margin) as u32,
+                ))
+                .expect("Unable to draw");
+            let left = x + (panel.cell - osd::GLYPH_WIDTH * scale) / 2;
+            let top = y + (panel.cell - osd::GLYPH_HEIGHT * scale) / 2;
+            self.canvas.set_draw_color(color(text));
+            for (column, row) in osd::text_pixels(&format!("{:X}", key)) {
+                self.canvas
+                    .fill_rect(Rect::new(
+                        (left + column * scale) as i32,
+                        (top + row * scale) as i32,
+                        scale as u32,
+                        scale as u32,
+                    ))
+                    .expect("Unable to draw");
+            }
+        }
+    }
+
+    fn draw_pixels(&mut self, screen: Rect, scale: usize) {
+        self.canvas.set_draw_color(color(self.palette.background()));
         self.canvas
-            .fill_rect(None)
+            .fill_rect(screen)
             .expect("Unable to fill rectangle on screen.");
-        self.canvas.set_draw_color(Color::RGB(255, 255, 255));
-        for (row_index, vec) in pixels.iter().enumerate() {
-            for (col_index, value) in vec.iter().enumerate() {
-                if *value == 1 {
+        for row_index in 0..HEIGHT {
+            for col_index in 0..WIDTH {
+                if let Some(rgba) = self.pixel_color(col_index, row_index) {
+                    self.canvas.set_draw_color(color(rgba));
                     self.canvas
                         .fill_rect(Rect::new(
-                            col_index as i32 * SCALING_FACTOR as i32,
-                            row_index as i32 * SCALING_FACTOR as i32,
-                            SCALING_FACTOR,
-                            SCALING_FACTOR,
+                            screen.x() + (col_index * scale) as i32,
+                            screen.y() + (row_index * scale) as i32,
+                            scale as u32,
+                            scale as u32,
                         ))
                         .expect("Unable to draw");
                 }
             }
         }
-        self.canvas.present();
+    }
+
+    // Draws the on-screen display in white on black boxes, at a quarter of
+    // the screen scale so that a line fits about 16 characters.
+    fn draw_osd(&mut self, screen: Rect, scale: usize) {
+        let size = (scale / 4).max(1);
+        let rect = |x: usize, y: usize, width: usize, height: usize| {
+            Rect::new(
+                screen.x() + (x * size) as i32,
+                screen.y() + (y * size) as i32,
+                (width * size) as u32,
+                (height * size) as u32,
+            )
+        };
+        for label in self.osd.labels(screen.height() as usize / size) {
+            self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+            self.canvas
+                .fill_rect(rect(
+                    label.x - 1,
+                    label.y - 1,
+                    osd::text_width(&label.text) + 2,
+                    LINE_HEIGHT,
+                ))
+                .expect("Unable to draw");
+            self.canvas.set_draw_color(Color::RGB(255, 255, 255));
+            for (x, y) in osd::text_pixels(&label.text) {
+                self.canvas
+                    .fill_rect(rect(label.x + x, label.y + y, 1, 1))
+                    .expect("Unable to draw");
+            }
+        }
+    }
+
+    // Renders the screen in software so the CRT filters can work on the
+    // image, then copies it to the window as a texture.
+    fn draw_filtered(&mut self, screen: Rect, scale: usize) -> Result<(), String> {
+        let mut rasterizer = Rasterizer::new(scale);
+        rasterizer.palette = self.palette;
+        let mut buffer = vec![0; rasterizer.buffer_len()];
+        rasterizer.render_with(&mut buffer, |x, y| {
+            self.pixel_color(x, y)
+                .unwrap_or_else(|| self.palette.background())
+        });
+        self.crt
+            .apply(&mut buffer, rasterizer.width(), rasterizer.height(), scale);
+
+        let texture_creator = self.canvas.texture_creator();
+        let mut texture = texture_creator
+            .create_texture_streaming(PixelFormatEnum::RGBA32, screen.width(), screen.height())
+            .map_err(|e| e.to_string())?;
+        texture
+            .update(None, &buffer, rasterizer.width() * 4)
+            .map_err(|e| e.to_string())?;
+        self.canvas.copy(&texture, None, screen)
+    }
+}
+
+impl VideoSink for Display {
+    fn present(&mut self, graphics: &Framebuffer) {
+        self.graphics = *graphics;
+        self.needs_draw = true;
+    }
+
+    // Redraws at most once per frame. The flicker filters change the screen
+    // every frame, even when the framebuffer stays the same.
+    fn end_frame(&mut self) {
+        if self.deflicker.filter != FlickerFilter::Off {
+            self.deflicker.push_frame(&self.graphics);
+            self.needs_draw = true;
+        }
+        if self.osd.end_frame() {
+            self.needs_draw = true;
+        }
+        self.draw_changes();
+    }
+
+    fn refresh(&mut self) {
+        self.needs_draw = true;
+        self.draw_changes();
+    }
+
+    fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.needs_draw = true;
+    }
+
+    fn set_keys(&mut self, keys: &[bool]) {
+        if self.keypad && self.keys[..] != *keys {
+            self.keys.copy_from_slice(keys);
+            self.needs_draw = true;
+        }
+    }
+
+    fn osd(&mut self) -> Option<&mut Osd> {
+        Some(&mut self.osd)
+    }
+
+    fn set_crt_filters(&mut self, filters: CrtFilters) {
+        self.crt = filters;
+        self.needs_draw = true;
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        let mode = if fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Off
+        };
+        if let Err(error) = self.canvas.window_mut().set_fullscreen(mode) {
+            eprintln!("Unable to switch fullscreen mode: {}", error);
+        }
     }
 }
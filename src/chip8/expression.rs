@@ -0,0 +1,440 @@
+use super::cpu::Cpu;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variable {
+    Pc,
+    I,
+    Sp,
+    DelayTimer,
+    SoundTimer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Array {
+    V,
+    Memory,
+    Stack,
+    Key,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Negate,
+    Complement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(i64),
+    Variable(Variable),
+    Index(Array, Box<Expression>),
+    Unary(UnaryOp, Box<Expression>),
+    Binary(BinaryOp, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub source: String,
+    pub position: usize,
+    pub length: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "error: {}", self.message)?;
+        writeln!(f, "  {}", self.source)?;
+        write!(
+            f,
+            "  {}{}",
+            " ".repeat(self.position),
+            "^".repeat(self.length.max(1))
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(i64),
+    Identifier(String),
+    Operator(&'static str),
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+    length: usize,
+}
+
+// Longer operators first so that "<=" is not read as "<" followed by "=".
+const OPERATORS: [&str; 24] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<<", ">>", "|", "^", "&", "<", ">", "+", "-", "*", "/",
+    "%", "!", "~", "(", ")", "[", "]",
+];
+
+// `0x1F` and `0X1F` alike.
+fn strip_radix_prefix(text: &str, letter: char) -> Option<&str> {
+    let rest = text.strip_prefix('0')?;
+    rest.strip_prefix(letter)
+        .or_else(|| rest.strip_prefix(letter.to_ascii_uppercase()))
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let c = bytes[position] as char;
+        if c.is_ascii_whitespace() {
+            position += 1;
+            continue;
+        }
+
+        let start = position;
+        if c.is_ascii_digit() {
+            while position < bytes.len() && (bytes[position] as char).is_ascii_alphanumeric() {
+                position += 1;
+            }
+            let text = &source[start..position];
+            let parsed = if let Some(hex) = strip_radix_prefix(text, 'x') {
+                i64::from_str_radix(hex, 16)
+            } else if let Some(binary) = strip_radix_prefix(text, 'b') {
+                i64::from_str_radix(binary, 2)
+            } else {
+                text.parse::<i64>()
+            };
+            let value = parsed.map_err(|_| ParseError {
+                source: source.to_string(),
+                position: start,
+                length: position - start,
+                message: format!("invalid number `{}`", text),
+            })?;
+            tokens.push(Token {
+                kind: TokenKind::Number(value),
+                position: start,
+                length: position - start,
+            });
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while position < bytes.len()
+                && ((bytes[position] as char).is_ascii_alphanumeric() || bytes[position] == b'_')
+            {
+                position += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Identifier(source[start..position].to_ascii_lowercase()),
+                position: start,
+                length: position - start,
+            });
+        } else {
+            let operator = OPERATORS
+                .iter()
+                .find(|op| source[start..].starts_with(*op))
+                .ok_or_else(|| ParseError {
+                    source: source.to_string(),
+                    position: start,
+                    length: 1,
+                    message: format!("unexpected character `{}`", c),
+                })?;
+            position += operator.len();
+            tokens.push(Token {
+                kind: TokenKind::Operator(operator),
+                position: start,
+                length: operator.len(),
+            });
+        }
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        position: source.len(),
+        length: 1,
+    });
+    Ok(tokens)
+}
+
+// Binary operators from lowest to highest precedence.
+const PRECEDENCE: [&[(&str, BinaryOp)]; 10] = [
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[("|", BinaryOp::BitOr)],
+    &[("^", BinaryOp::BitXor)],
+    &[("&", BinaryOp::BitAnd)],
+    &[("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)],
+    &[
+        ("<", BinaryOp::Less),
+        ("<=", BinaryOp::LessEqual),
+        (">", BinaryOp::Greater),
+        (">=", BinaryOp::GreaterEqual),
+    ],
+    &[("<<", BinaryOp::ShiftLeft), (">>", BinaryOp::ShiftRight)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+    &[
+        ("*", BinaryOp::Multiply),
+        ("/", BinaryOp::Divide),
+        ("%", BinaryOp::Remainder),
+    ],
+];
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error(&self, token: &Token, message: String) -> ParseError {
+        ParseError {
+            source: self.source.to_string(),
+            position: token.position,
+            length: token.length,
+            message,
+        }
+    }
+
+    fn describe(token: &Token) -> String {
+        match &token.kind {
+            TokenKind::Number(value) => format!("number `{}`", value),
+            TokenKind::Identifier(name) => format!("`{}`", name),
+            TokenKind::Operator(op) => format!("`{}`", op),
+            TokenKind::End => String::from("end of input"),
+        }
+    }
+
+    fn expect(&mut self, operator: &str) -> Result<(), ParseError> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Operator(op) if op == operator => Ok(()),
+            _ => Err(self.error(
+                &token,
+                format!(
+                    "expected `{}`, found {}",
+                    operator,
+                    Parser::describe(&token)
+                ),
+            )),
+        }
+    }
+
+    fn parse_binary(&mut self, level: usize) -> Result<Expression, ParseError> {
+        if level == PRECEDENCE.len() {
+            return self.parse_unary();
+        }
+
+        let mut lhs = self.parse_binary(level + 1)?;
+        loop {
+            let op = match &self.peek().kind {
+                TokenKind::Operator(token) => PRECEDENCE[level]
+                    .iter()
+                    .find(|(symbol, _)| symbol == token)
+                    .map(|(_, op)| *op),
+                _ => None,
+            };
+            match op {
+                Some(op) => {
+                    self.advance();
+                    let rhs = self.parse_binary(level + 1)?;
+                    lhs = Expression::Binary(op, Box::new(lhs), Box::new(rhs));
+                }
+                None => return Ok(lhs),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, ParseError> {
+        let op = match self.peek().kind {
+            TokenKind::Operator("!") => Some(UnaryOp::Not),
+            TokenKind::Operator("-") => Some(UnaryOp::Negate),
+            TokenKind::Operator("~") => Some(UnaryOp::Complement),
+            _ => None,
+        };
+        match op {
+            Some(op) => {
+                self.advance();
+                Ok(Expression::Unary(op, Box::new(self.parse_unary()?)))
+            }
+            None => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+        let token = self.advance();
+        match &token.kind {
+            TokenKind::Number(value) => Ok(Expression::Literal(*value)),
+            TokenKind::Operator("(") => {
+                let inner = self.parse_binary(0)?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            TokenKind::Identifier(name) => {
+                let variable = match name.as_str() {
+                    "pc" => Some(Variable::Pc),
+                    "i" => Some(Variable::I),
+                    "sp" => Some(Variable::Sp),
+                    "dt" => Some(Variable::DelayTimer),
+                    "st" => Some(Variable::SoundTimer),
+                    _ => None,
+                };
+                if let Some(variable) = variable {
+                    return Ok(Expression::Variable(variable));
+                }
+
+                let array = match name.as_str() {
+                    "v" => Some(Array::V),
+                    "mem" => Some(Array::Memory),
+                    "stack" => Some(Array::Stack),
+                    "key" => Some(Array::Key),
+                    _ => None,
+                };
+                if let Some(array) = array {
+                    self.expect("[")?;
+                    let index = self.parse_binary(0)?;
+                    self.expect("]")?;
+                    return Ok(Expression::Index(array, Box::new(index)));
+                }
+
+                // Shorthand register names such as `v3` or `vf`.
+                if name.len() == 2 && name.starts_with('v') {
+                    if let Ok(register) = i64::from_str_radix(&name[1..], 16) {
+                        return Ok(Expression::Index(
+                            Array::V,
+                            Box::new(Expression::Literal(register)),
+                        ));
+                    }
+                }
+
+                Err(self.error(&token, format!("unknown identifier `{}`", name)))
+            }
+            _ => Err(self.error(
+                &token,
+                format!("expected expression, found {}", Parser::describe(&token)),
+            )),
+        }
+    }
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, ParseError> {
+        let mut parser = Parser {
+            source,
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let expression = parser.parse_binary(0)?;
+        let trailing = parser.advance();
+        if trailing.kind != TokenKind::End {
+            return Err(parser.error(
+                &trailing,
+                format!("unexpected {}", Parser::describe(&trailing)),
+            ));
+        }
+        Ok(expression)
+    }
+
+    // Out-of-range reads evaluate to 0, as do division and remainder by zero,
+    // so that a condition never aborts the emulation.
+    pub fn evaluate(&self, cpu: &Cpu) -> i64 {
+        match self {
+            Expression::Literal(value) => *value,
+            Expression::Variable(variable) => match variable {
+                Variable::Pc => cpu.pc as i64,
+                Variable::I => cpu.i as i64,
+                Variable::Sp => cpu.sp as i64,
+                Variable::DelayTimer => cpu.delay_timer as i64,
+                Variable::SoundTimer => cpu.sound_timer as i64,
+            },
+            Expression::Index(array, index) => {
+                let index = index.evaluate(cpu);
+                if index < 0 {
+                    return 0;
+                }
+                let index = index as usize;
+                match array {
+                    Array::V => cpu.v.get(index).map_or(0, |value| *value as i64),
+                    Array::Memory => cpu.memory.get(index).map_or(0, |value| *value as i64),
+                    Array::Stack => cpu.stack.get(index).map_or(0, |value| *value as i64),
+                    Array::Key => cpu.key.get(index).map_or(0, |value| *value as i64),
+                }
+            }
+            Expression::Unary(op, operand) => {
+                let value = operand.evaluate(cpu);
+                match op {
+                    UnaryOp::Not => (value == 0) as i64,
+                    UnaryOp::Negate => value.wrapping_neg(),
+                    UnaryOp::Complement => !value,
+                }
+            }
+            Expression::Binary(BinaryOp::And, lhs, rhs) => {
+                (lhs.evaluate(cpu) != 0 && rhs.evaluate(cpu) != 0) as i64
+            }
+            Expression::Binary(BinaryOp::Or, lhs, rhs) => {
+                (lhs.evaluate(cpu) != 0 || rhs.evaluate(cpu) != 0) as i64
+            }
+            Expression::Binary(op, lhs, rhs) => {
+                let lhs = lhs.evaluate(cpu);
+                let rhs = rhs.evaluate(cpu);
+                match op {
+                    BinaryOp::BitOr => lhs | rhs,
+                    BinaryOp::BitXor => lhs ^ rhs,
+                    BinaryOp::BitAnd => lhs & rhs,
+                    BinaryOp::Equal => (lhs == rhs) as i64,
+                    BinaryOp::NotEqual => (lhs != rhs) as i64,
+                    BinaryOp::Less => (lhs < rhs) as i64,
+                    BinaryOp::LessEqual => (lhs <= rhs) as i64,
+                    BinaryOp::Greater => (lhs > rhs) as i64,
+                    BinaryOp::GreaterEqual => (lhs >= rhs) as i64,
+                    BinaryOp::ShiftLeft => lhs.wrapping_shl(rhs as u32),
+                    BinaryOp::ShiftRight => lhs.wrapping_shr(rhs as u32),
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Subtract => lhs.wrapping_sub(rhs),
+                    BinaryOp::Multiply => lhs.wrapping_mul(rhs),
+                    BinaryOp::Divide => lhs.checked_div(rhs).unwrap_or(0),
+                    BinaryOp::Remainder => lhs.checked_rem(rhs).unwrap_or(0),
+                    BinaryOp::And | BinaryOp::Or => unreachable!(),
+                }
+            }
+        }
+    }
+
+    pub fn is_true(&self, cpu: &Cpu) -> bool {
+        self.evaluate(cpu) != 0
+    }
+}
@@ -0,0 +1,93 @@
+use super::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use alloc::format;
+use alloc::string::String;
+
+// Ways to hide the flicker of sprites that are erased and redrawn with XOR.
+// Strengths are percentages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlickerFilter {
+    Off,
+    // Lit pixels light up fully and then fade, keeping `persistence` percent
+    // of their brightness every frame.
+    Phosphor { persistence: u8 },
+    // Pixels that were lit in the previous frame but not in this one are
+    // drawn at `strength` percent.
+    Blend { strength: u8 },
+}
+
+impl FlickerFilter {
+    // Parses `off`, `phosphor`, `blend`, or either of the latter two followed
+    // by `:PERCENT`.
+    pub fn parse(text: &str) -> Result<FlickerFilter, String> {
+        let (name, strength) = match text.split_once(':') {
+            Some((name, strength)) => match strength.parse::<u8>() {
+                Ok(strength) if strength <= 100 => (name, Some(strength)),
+                _ => return Err(format!("Strength `{}` is not a percentage.", strength)),
+            },
+            None => (text, None),
+        };
+        match name {
+            "off" if strength.is_none() => Ok(FlickerFilter::Off),
+            "phosphor" => Ok(FlickerFilter::Phosphor {
+                persistence: strength.unwrap_or(60),
+            }),
+            "blend" => Ok(FlickerFilter::Blend {
+                strength: strength.unwrap_or(50),
+            }),
+            _ => Err(format!(
+                "Unknown flicker filter `{}`. Use off, phosphor[:PERCENT] or blend[:PERCENT].",
+                text
+            )),
+        }
+    }
+}
+
+// Keeps the brightness of every pixel across 60 Hz frames. Each pixel has the
+// value it was last lit with, which picks its palette colour, and a level from
+// 0 (background) to 255 (fully lit).
+pub struct Deflicker {
+    pub filter: FlickerFilter,
+    values: [[u8; WIDTH]; HEIGHT],
+    levels: [[u8; WIDTH]; HEIGHT],
+    previous: Framebuffer,
+}
+
+impl Deflicker {
+    pub fn new(filter: FlickerFilter) -> Deflicker {
+        Deflicker {
+            filter,
+            values: [[0; WIDTH]; HEIGHT],
+            levels: [[0; WIDTH]; HEIGHT],
+            previous: Framebuffer::new(),
+        }
+    }
+
+    pub fn push_frame(&mut self, graphics: &Framebuffer) {
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let pixel = graphics.pixel(x, y);
+                let level = self.levels[y][x] as u32;
+                self.levels[y][x] = match self.filter {
+                    _ if pixel != 0 => 255,
+                    FlickerFilter::Off => 0,
+                    FlickerFilter::Phosphor { persistence } => {
+                        (level * persistence as u32 / 100) as u8
+                    }
+                    FlickerFilter::Blend { strength } if self.previous.pixel(x, y) != 0 => {
+                        (255 * strength as u32 / 100) as u8
+                    }
+                    FlickerFilter::Blend { .. } => 0,
+                };
+                if pixel != 0 {
+                    self.values[y][x] = pixel;
+                }
+            }
+        }
+        self.previous = *graphics;
+    }
+
+    // The palette value and brightness of a pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> (u8, u8) {
+        (self.values[y][x], self.levels[y][x])
+    }
+}
@@ -0,0 +1,70 @@
+pub const WIDTH: usize = 64;
+pub const HEIGHT: usize = 32;
+
+// One bit per pixel. The most significant bit is the leftmost pixel.
+pub type Row = u64;
+
+// Packed monochrome screen. Sprites are drawn a whole row at a time, and
+// frontends read single pixels through `pixel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Framebuffer {
+    rows: [Row; HEIGHT],
+}
+
+impl Default for Framebuffer {
+    fn default() -> Framebuffer {
+        Framebuffer::new()
+    }
+}
+
+impl Framebuffer {
+    pub const fn new() -> Framebuffer {
+        Framebuffer { rows: [0; HEIGHT] }
+    }
+
+    pub const fn from_rows(rows: [Row; HEIGHT]) -> Framebuffer {
+        Framebuffer { rows }
+    }
+
+    pub fn rows(&self) -> &[Row; HEIGHT] {
+        &self.rows
+    }
+
+    pub fn row(&self, y: usize) -> Row {
+        self.rows[y]
+    }
+
+    fn mask(x: usize) -> Row {
+        1 << (WIDTH - 1 - x)
+    }
+
+    // 1 if the pixel is lit and 0 otherwise, which doubles as palette index.
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        (self.rows[y] & Framebuffer::mask(x) != 0) as u8
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, lit: bool) {
+        if lit {
+            self.rows[y] |= Framebuffer::mask(x);
+        } else {
+            self.rows[y] &= !Framebuffer::mask(x);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.rows = [0; HEIGHT];
+    }
+
+    pub fn lit_pixels(&self) -> usize {
+        self.rows.iter().map(|row| row.count_ones() as usize).sum()
+    }
+
+    // XORs an 8 pixel sprite row onto row `y` starting at column `x`, wrapping
+    // around the right edge. Returns whether a lit pixel was switched off.
+    pub fn draw_sprite_row(&mut self, x: usize, y: usize, sprite: u8) -> bool {
+        let bits = ((sprite as Row) << (WIDTH - 8)).rotate_right((x % WIDTH) as u32);
+        let collision = self.rows[y] & bits != 0;
+        self.rows[y] ^= bits;
+        collision
+    }
+}
@@ -0,0 +1,91 @@
+use super::cpu::Cpu;
+use super::crt::{CrtEffect, CrtFilters};
+use super::framebuffer::Framebuffer;
+use super::osd::Osd;
+use super::raster::Palette;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostAction {
+    Quit,
+    Break,
+    Screenshot,
+    ToggleRecording,
+    NextPalette,
+    ToggleFullscreen,
+    ToggleCrt(CrtEffect),
+    ToggleCounters,
+    None,
+}
+
+// Receives the screen at most once per 60 Hz frame, and only when it changed.
+pub trait VideoSink {
+    fn present(&mut self, graphics: &Framebuffer);
+
+    // Called at the end of every 60 Hz frame, also when nothing was presented,
+    // for sinks that animate or need a constant frame rate.
+    fn end_frame(&mut self) {}
+
+    // Shows what was presented without ending a frame, while the debugger
+    // holds execution. Sinks that count frames must not count this.
+    fn refresh(&mut self) {}
+
+    // Called when the debugger gives control back, for sinks that share the
+    // terminal with it and have to draw everything again.
+    fn resume(&mut self) {}
+
+    // Sinks without colour can ignore palette changes.
+    fn set_palette(&mut self, _: Palette) {}
+
+    fn set_fullscreen(&mut self, _: bool) {}
+
+    fn set_crt_filters(&mut self, _: CrtFilters) {}
+
+    // The state of the 16 keys at the end of every frame, for sinks that
+    // show the keypad.
+    fn set_keys(&mut self, _: &[bool]) {}
+
+    // Sinks that can draw text over the screen return their on-screen
+    // display.
+    fn osd(&mut self) -> Option<&mut Osd> {
+        None
+    }
+}
+
+// Turns the buzzer on while the sound timer is running.
+pub trait AudioSink {
+    fn set_tone(&mut self, playing: bool);
+
+    // Called once per step after `set_tone`, for sinks that generate samples
+    // and have to keep time with the emulator.
+    fn tick(&mut self) {}
+}
+
+// Polled once per step. Updates the keypad of the CPU and reports requests
+// from the host such as quitting or breaking into the debugger.
+pub trait InputSource {
+    fn poll(&mut self, cpu: &mut Cpu) -> HostAction;
+
+    // Called before the debugger reads from stdin. Sources that take over
+    // the terminal give it back until the next poll.
+    fn suspend(&mut self) {}
+}
+
+pub struct NoVideo;
+
+impl VideoSink for NoVideo {
+    fn present(&mut self, _: &Framebuffer) {}
+}
+
+pub struct NoAudio;
+
+impl AudioSink for NoAudio {
+    fn set_tone(&mut self, _: bool) {}
+}
+
+pub struct NoInput;
+
+impl InputSource for NoInput {
+    fn poll(&mut self, _: &mut Cpu) -> HostAction {
+        HostAction::None
+    }
+}
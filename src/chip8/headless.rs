@@ -0,0 +1,213 @@
+use super::cpu::Cpu;
+use super::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use super::frontend::{HostAction, InputSource};
+
+// The main loop runs 360 steps per second, so one 60 Hz frame is six steps.
+pub const STEPS_PER_FRAME: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub frame: usize,
+    pub key: usize,
+    pub pressed: bool,
+}
+
+// Key presses and releases applied at the start of the given frames.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputScript {
+    events: Vec<InputEvent>,
+}
+
+impl InputScript {
+    pub fn new() -> InputScript {
+        InputScript::default()
+    }
+
+    pub fn press(&mut self, frame: usize, key: usize) {
+        self.push(frame, key, true);
+    }
+
+    pub fn release(&mut self, frame: usize, key: usize) {
+        self.push(frame, key, false);
+    }
+
+    fn push(&mut self, frame: usize, key: usize, pressed: bool) {
+        let event = InputEvent {
+            frame,
+            key,
+            pressed,
+        };
+        let position = self.events.partition_point(|other| other.frame <= frame);
+        self.events.insert(position, event);
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    // Applies the events up to `frame`, starting at `next_event`.
+    fn apply(&self, next_event: &mut usize, frame: usize, cpu: &mut Cpu) {
+        while let Some(event) = self.events.get(*next_event) {
+            if event.frame > frame {
+                break;
+            }
+            cpu.set_key(event.key, event.pressed);
+            *next_event += 1;
+        }
+    }
+
+    // One event per line: `FRAME KEY down` or `FRAME KEY up`, keys in hex.
+    pub fn parse(text: &str) -> Result<InputScript, String> {
+        let mut script = InputScript::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let event = match parts.as_slice() {
+                [frame, key, state] => (
+                    frame.parse::<usize>().ok(),
+                    usize::from_str_radix(key, 16).ok().filter(|key| *key < 16),
+                    match *state {
+                        "down" => Some(true),
+                        "up" => Some(false),
+                        _ => None,
+                    },
+                ),
+                _ => (None, None, None),
+            };
+            match event {
+                (Some(frame), Some(key), Some(pressed)) => script.push(frame, key, pressed),
+                _ => {
+                    return Err(format!(
+                        "Line {}: expected `FRAME KEY down` or `FRAME KEY up`.",
+                        number + 1
+                    ))
+                }
+            }
+        }
+        Ok(script)
+    }
+}
+
+pub struct HeadlessResult {
+    pub frames: usize,
+    pub condition_met: bool,
+    pub graphics: Framebuffer,
+    pub cpu: Cpu,
+}
+
+impl HeadlessResult {
+    pub fn screen(&self) -> String {
+        let mut screen = String::new();
+        for y in 0..HEIGHT {
+            screen.extend((0..WIDTH).map(|x| match self.graphics.pixel(x, y) {
+                0 => '.',
+                _ => '#',
+            }));
+            screen.push('\n');
+        }
+        screen
+    }
+}
+
+// Runs a ROM without a window, audio or real time, one frame at a time.
+pub struct Headless {
+    pub cpu: Cpu,
+    pub frame: usize,
+    input: InputScript,
+    next_event: usize,
+}
+
+impl Headless {
+    pub fn new(rom: &[u8]) -> Headless {
+        let mut cpu = Cpu::initialize();
+        cpu.load_program(rom);
+        Headless {
+            cpu,
+            frame: 0,
+            input: InputScript::new(),
+            next_event: 0,
+        }
+    }
+
+    pub fn set_input(&mut self, input: InputScript) {
+        self.input = input;
+        self.next_event = self
+            .input
+            .events
+            .partition_point(|event| event.frame < self.frame);
+    }
+
+    pub fn run_frame(&mut self) {
+        self.input
+            .apply(&mut self.next_event, self.frame, &mut self.cpu);
+        for _ in 0..STEPS_PER_FRAME {
+            self.cpu.step();
+        }
+        self.frame += 1;
+    }
+
+    pub fn run_frames(self, frames: usize) -> HeadlessResult {
+        self.run_until(frames, |_| false)
+    }
+
+    // Stops after `max_frames` frames or as soon as the condition holds at the
+    // end of a frame, whichever comes first.
+    pub fn run_until<F>(mut self, max_frames: usize, mut condition: F) -> HeadlessResult
+    where
+        F: FnMut(&Cpu) -> bool,
+    {
+        let mut frames = 0;
+        let mut condition_met = false;
+        while frames < max_frames {
+            self.run_frame();
+            frames += 1;
+            if condition(&self.cpu) {
+                condition_met = true;
+                break;
+            }
+        }
+        HeadlessResult {
+            frames,
+            condition_met,
+            graphics: self.cpu.graphics,
+            cpu: self.cpu,
+        }
+    }
+}
+
+// Plays an input script inside `Emulator::run` and quits after `frames`
+// frames, so the regular run loop can be driven without a keyboard.
+pub struct ScriptedInput {
+    script: InputScript,
+    next_event: usize,
+    steps: usize,
+    frames: usize,
+}
+
+impl ScriptedInput {
+    pub fn new(script: InputScript, frames: usize) -> ScriptedInput {
+        ScriptedInput {
+            script,
+            next_event: 0,
+            steps: 0,
+            frames,
+        }
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self, cpu: &mut Cpu) -> HostAction {
+        if self.steps.is_multiple_of(STEPS_PER_FRAME) {
+            let frame = self.steps / STEPS_PER_FRAME;
+            if frame >= self.frames {
+                return HostAction::Quit;
+            }
+            self.script.apply(&mut self.next_event, frame, cpu);
+        }
+        self.steps += 1;
+        HostAction::None
+    }
+}
@@ -0,0 +1,107 @@
+use super::keypad::LAYOUT;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// The keys of the usual layout, in the same places as the keypad in `LAYOUT`.
+// As scancodes they name the same positions on other layouts.
+const STANDARD_KEYS: [[&str; 4]; 4] = [
+    ["1", "2", "3", "4"],
+    ["Q", "W", "E", "R"],
+    ["A", "S", "D", "F"],
+    ["Z", "X", "C", "V"],
+];
+
+// Whether frontends name keys by their position on the keyboard, which works
+// the same on every layout, or by the character they type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMatch {
+    Scancode,
+    Keycode,
+}
+
+impl KeyMatch {
+    pub fn parse(text: &str) -> Result<KeyMatch, String> {
+        match text {
+            "scancode" => Ok(KeyMatch::Scancode),
+            "keycode" => Ok(KeyMatch::Keycode),
+            _ => Err(format!(
+                "Unknown key matching `{}`. Use scancode or keycode.",
+                text
+            )),
+        }
+    }
+}
+
+// Maps host key names to the 16 hex keys. Names are compared without case,
+// and any number of host keys can press the same hex key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(String, usize)>,
+    pub matching: KeyMatch,
+}
+
+impl Default for Keymap {
+    // The 4x4 block from 1 to V on a QWERTY keyboard.
+    fn default() -> Keymap {
+        let mut keymap = Keymap::empty();
+        for (keys, names) in LAYOUT.iter().zip(STANDARD_KEYS.iter()) {
+            for (key, name) in keys.iter().zip(names.iter()) {
+                keymap.bindings.push((name.to_string(), *key));
+            }
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    pub fn empty() -> Keymap {
+        Keymap {
+            bindings: Vec::new(),
+            matching: KeyMatch::Scancode,
+        }
+    }
+
+    pub fn key(&self, name: &str) -> Option<usize> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound.eq_ignore_ascii_case(name))
+            .map(|(_, key)| *key)
+    }
+
+    pub fn names(&self, key: usize) -> impl Iterator<Item = &str> {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == key)
+            .map(|(name, _)| name.as_str())
+    }
+
+    // Replaces the host keys of `key`. The names stop pressing any other key.
+    pub fn bind(&mut self, key: usize, names: &[&str]) {
+        self.bindings.retain(|(bound_name, bound_key)| {
+            *bound_key != key
+                && !names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(bound_name))
+        });
+        for name in names {
+            self.bindings.push((name.to_string(), key));
+        }
+    }
+
+    // Applies one line of a config file: a hex digit and either one key name
+    // or an array of quoted names, e.g. `5 = ["W", "Up"]`.
+    pub fn configure(&mut self, key: &str, names: &str) -> Result<(), String> {
+        let key = match usize::from_str_radix(key, 16) {
+            Ok(key) if key < 16 => key,
+            _ => return Err(format!("`{}` is not a hex key from 0 to F.", key)),
+        };
+        let names: Vec<&str> = match names.strip_prefix('[') {
+            Some(array) => array.split('"').skip(1).step_by(2).collect(),
+            None => vec![names],
+        };
+        self.bind(key, &names);
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use super::framebuffer::{HEIGHT, WIDTH};
+use super::raster::{letterbox_area, Viewport};
+
+// The COSMAC VIP hex keypad, row by row.
+pub const LAYOUT: [[usize; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xc],
+    [0x4, 0x5, 0x6, 0xd],
+    [0x7, 0x8, 0x9, 0xe],
+    [0xa, 0x0, 0xb, 0xf],
+];
+
+// The keypad is a square as high as the screen, in CHIP-8 pixels.
+pub const PANEL_SIZE: usize = HEIGHT;
+const CELL_SIZE: usize = PANEL_SIZE / 4;
+
+// Where the keypad goes in the window, in window pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeypadPanel {
+    pub x: usize,
+    pub y: usize,
+    // Size of one key including its border.
+    pub cell: usize,
+}
+
+// Letterboxes the screen with the keypad to its right.
+pub fn layout(width: usize, height: usize) -> (Viewport, KeypadPanel) {
+    let viewport = letterbox_area(width, height, WIDTH + PANEL_SIZE, HEIGHT);
+    let panel = KeypadPanel {
+        x: viewport.x + WIDTH * viewport.scale,
+        y: viewport.y,
+        cell: CELL_SIZE * viewport.scale,
+    };
+    (viewport, panel)
+}
+
+impl KeypadPanel {
+    pub fn key_at(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.x || y < self.y {
+            return None;
+        }
+        let column = (x - self.x) / self.cell;
+        let row = (y - self.y) / self.cell;
+        LAYOUT.get(row).and_then(|keys| keys.get(column)).copied()
+    }
+
+    // The top left corner of a key.
+    pub fn key_position(&self, key: usize) -> (usize, usize) {
+        let index = LAYOUT.iter().flatten().position(|k| *k == key).unwrap_or(0);
+        (
+            self.x + index % 4 * self.cell,
+            self.y + index / 4 * self.cell,
+        )
+    }
+}
@@ -0,0 +1,292 @@
+use super::analysis::{
+    memory_read_length, memory_write_length, ControlFlowGraph, EdgeKind, MEMORY_SIZE, PROGRAM_START,
+};
+use super::cpu::Instruction;
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const STACK_SIZE: usize = 16;
+// `CALL` moves the stack pointer up before storing the return address, so the
+// first entry stays unused and the 16th nested call runs off the stack.
+pub const MAX_CALL_DEPTH: usize = STACK_SIZE - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    CallWithoutReturn {
+        address: usize,
+        subroutine: usize,
+    },
+    ReturnWithoutCall {
+        address: usize,
+    },
+    MisalignedJump {
+        address: usize,
+        target: usize,
+    },
+    MisalignedSkip {
+        address: usize,
+        target: usize,
+    },
+    JumpOutsideProgram {
+        address: usize,
+        target: usize,
+    },
+    WriteOverlapsCode {
+        address: usize,
+        target: usize,
+        length: usize,
+    },
+    ReadPastMemory {
+        address: usize,
+        target: usize,
+        length: usize,
+    },
+    WritePastMemory {
+        address: usize,
+        target: usize,
+        length: usize,
+    },
+    UnknownOpcode {
+        address: usize,
+        opcode: u16,
+    },
+    StackOverflow {
+        address: usize,
+        depth: usize,
+    },
+    Recursion {
+        address: usize,
+        subroutine: usize,
+    },
+}
+
+impl Lint {
+    pub fn address(&self) -> usize {
+        match *self {
+            Lint::CallWithoutReturn { address, .. }
+            | Lint::ReturnWithoutCall { address }
+            | Lint::MisalignedJump { address, .. }
+            | Lint::MisalignedSkip { address, .. }
+            | Lint::JumpOutsideProgram { address, .. }
+            | Lint::WriteOverlapsCode { address, .. }
+            | Lint::ReadPastMemory { address, .. }
+            | Lint::WritePastMemory { address, .. }
+            | Lint::UnknownOpcode { address, .. }
+            | Lint::StackOverflow { address, .. }
+            | Lint::Recursion { address, .. } => address,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:03X}: ", self.address())?;
+        match *self {
+            Lint::CallWithoutReturn { subroutine, .. } => {
+                write!(f, "call to 0x{:03X}, which never returns", subroutine)
+            }
+            Lint::ReturnWithoutCall { .. } => write!(f, "return with an empty stack"),
+            Lint::MisalignedJump { target, .. } => write!(
+                f,
+                "jump to 0x{:03X}, the middle of the instruction at 0x{:03X}",
+                target,
+                target - 1
+            ),
+            Lint::MisalignedSkip { target, .. } => write!(
+                f,
+                "skip lands at 0x{:03X}, the middle of the instruction at 0x{:03X}",
+                target,
+                target - 1
+            ),
+            Lint::JumpOutsideProgram { target, .. } if target + 1 >= MEMORY_SIZE => {
+                write!(f, "jump to 0x{:03X}, outside of memory", target)
+            }
+            Lint::JumpOutsideProgram { target, .. } => {
+                write!(f, "jump to 0x{:03X}, outside of the ROM", target)
+            }
+            Lint::WriteOverlapsCode { target, length, .. } => write!(
+                f,
+                "writes {} bytes at I = 0x{:03X}, overlapping code",
+                length, target
+            ),
+            Lint::ReadPastMemory { target, length, .. } => write!(
+                f,
+                "reads {} bytes at I = 0x{:03X}, past the end of memory",
+                length, target
+            ),
+            Lint::WritePastMemory { target, length, .. } => write!(
+                f,
+                "writes {} bytes at I = 0x{:03X}, past the end of memory",
+                length, target
+            ),
+            Lint::UnknownOpcode { opcode, .. } => {
+                write!(f, "unknown opcode 0x{:04X} in reachable code", opcode)
+            }
+            Lint::StackOverflow { depth, .. } => write!(
+                f,
+                "call nesting reaches depth {}, more than the {} calls the stack holds",
+                depth, MAX_CALL_DEPTH
+            ),
+            Lint::Recursion { subroutine, .. } => write!(
+                f,
+                "recursive call to 0x{:03X} can overflow the stack",
+                subroutine
+            ),
+        }
+    }
+}
+
+struct Linter<'a> {
+    graph: &'a ControlFlowGraph,
+    // Call sites of every function, keyed by function entry.
+    calls: BTreeMap<usize, Vec<(usize, usize)>>,
+    deepest: BTreeMap<usize, usize>,
+    lints: Vec<Lint>,
+}
+
+impl<'a> Linter<'a> {
+    fn check_functions(&mut self) {
+        let graph = self.graph;
+        let entries = graph.subroutines.iter().chain([PROGRAM_START].iter());
+        for entry in entries.filter(|entry| graph.instructions.contains_key(entry)) {
+            let body = graph.function_body(*entry);
+            let mut returns = false;
+            let mut calls = Vec::new();
+            for address in &body {
+                match graph.instructions[address] {
+                    Instruction::Ret if *entry == PROGRAM_START => self
+                        .lints
+                        .push(Lint::ReturnWithoutCall { address: *address }),
+                    Instruction::Ret => returns = true,
+                    Instruction::Call(subroutine) => calls.push((*address, subroutine)),
+                    _ => {}
+                }
+            }
+            if !returns && *entry != PROGRAM_START {
+                let call_sites = graph
+                    .edges
+                    .values()
+                    .flatten()
+                    .filter(|edge| edge.kind == EdgeKind::Call && edge.target == *entry);
+                for edge in call_sites {
+                    self.lints.push(Lint::CallWithoutReturn {
+                        address: edge.source,
+                        subroutine: *entry,
+                    });
+                }
+            }
+            self.calls.insert(*entry, calls);
+        }
+    }
+
+    fn check_edges(&mut self) {
+        for edge in self.graph.edges.values().flatten() {
+            if !self.graph.instructions.contains_key(&(edge.target - 1)) {
+                continue;
+            }
+            let (address, target) = (edge.source, edge.target);
+            match edge.kind {
+                EdgeKind::Jump | EdgeKind::Call => {
+                    self.lints.push(Lint::MisalignedJump { address, target })
+                }
+                EdgeKind::Skip => self.lints.push(Lint::MisalignedSkip { address, target }),
+                EdgeKind::Fallthrough => {}
+            }
+        }
+        for edge in &self.graph.exits {
+            self.lints.push(Lint::JumpOutsideProgram {
+                address: edge.source,
+                target: edge.target,
+            });
+        }
+    }
+
+    fn check_instructions(&mut self) {
+        for (address, instruction) in &self.graph.instructions {
+            if let Instruction::Unknown(opcode) = instruction {
+                self.lints.push(Lint::UnknownOpcode {
+                    address: *address,
+                    opcode: *opcode,
+                });
+            }
+            let target = match self.graph.known_i(*address) {
+                Some(target) => target,
+                None => continue,
+            };
+            if let Some(length) = memory_read_length(*instruction) {
+                if target + length > MEMORY_SIZE {
+                    self.lints.push(Lint::ReadPastMemory {
+                        address: *address,
+                        target,
+                        length,
+                    });
+                }
+            }
+            if let Some(length) = memory_write_length(*instruction) {
+                if target + length > MEMORY_SIZE {
+                    self.lints.push(Lint::WritePastMemory {
+                        address: *address,
+                        target,
+                        length,
+                    });
+                }
+            }
+        }
+        for modification in &self.graph.self_modifying {
+            self.lints.push(Lint::WriteOverlapsCode {
+                address: modification.address,
+                target: modification.target,
+                length: modification.length,
+            });
+        }
+    }
+
+    // Walks the call graph from the entry point, tracking the call path so
+    // that recursion and nesting deeper than the stack are both reported.
+    fn check_stack(&mut self, entry: usize, depth: usize, path: &mut Vec<usize>) {
+        if self
+            .deepest
+            .get(&entry)
+            .is_some_and(|deepest| *deepest >= depth)
+        {
+            return;
+        }
+        self.deepest.insert(entry, depth);
+        path.push(entry);
+        let calls = self.calls.get(&entry).cloned().unwrap_or_default();
+        for (address, subroutine) in calls {
+            if path.contains(&subroutine) {
+                self.lints.push(Lint::Recursion {
+                    address,
+                    subroutine,
+                });
+            } else if depth + 1 > MAX_CALL_DEPTH {
+                self.lints.push(Lint::StackOverflow {
+                    address,
+                    depth: depth + 1,
+                });
+            } else {
+                self.check_stack(subroutine, depth + 1, path);
+            }
+        }
+        path.pop();
+    }
+}
+
+pub fn lint(graph: &ControlFlowGraph) -> Vec<Lint> {
+    let mut linter = Linter {
+        graph,
+        calls: BTreeMap::new(),
+        deepest: BTreeMap::new(),
+        lints: Vec::new(),
+    };
+    linter.check_functions();
+    linter.check_edges();
+    linter.check_instructions();
+    linter.check_stack(PROGRAM_START, 0, &mut Vec::new());
+
+    let mut lints = linter.lints;
+    lints.sort_by_key(|lint| lint.address());
+    lints.dedup();
+    lints
+}
@@ -0,0 +1,208 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+// On-screen display: short messages in the top left corner, and the platform
+// profile at the bottom with the speed counters above it when they are on. Positions are in
+// font pixels; the frontend picks how large a font pixel is.
+pub const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+pub const ADVANCE: usize = GLYPH_WIDTH + 1;
+pub const LINE_HEIGHT: usize = GLYPH_HEIGHT + 2;
+// Two seconds at 60 Hz.
+pub const MESSAGE_FRAMES: u32 = 120;
+pub const MAX_MESSAGES: usize = 3;
+
+// 3x5 glyphs, five rows of three bits with the top left pixel in the highest
+// bit. Lower case letters use the upper case glyphs and unknown characters
+// are drawn as `?`.
+const FONT: [(char, u16); 56] = [
+    (' ', 0b000000000000000),
+    ('!', 0b010010010000010),
+    ('#', 0b101111101111101),
+    ('%', 0b101001010100101),
+    ('\'', 0b010010000000000),
+    ('(', 0b010100100100010),
+    (')', 0b010001001001010),
+    ('+', 0b000010111010000),
+    (',', 0b000000000010100),
+    ('-', 0b000000111000000),
+    ('.', 0b000000000000010),
+    ('/', 0b001001010100100),
+    ('0', 0b111101101101111),
+    ('1', 0b010110010010111),
+    ('2', 0b111001111100111),
+    ('3', 0b111001111001111),
+    ('4', 0b101101111001001),
+    ('5', 0b111100111001111),
+    ('6', 0b111100111101111),
+    ('7', 0b111001001001001),
+    ('8', 0b111101111101111),
+    ('9', 0b111101111001111),
+    (':', 0b000010000010000),
+    ('<', 0b001010100010001),
+    ('=', 0b000111000111000),
+    ('>', 0b100010001010100),
+    ('?', 0b111001011000010),
+    ('A', 0b111101111101101),
+    ('B', 0b110101110101110),
+    ('C', 0b111100100100111),
+    ('D', 0b110101101101110),
+    ('E', 0b111100110100111),
+    ('F', 0b111100110100100),
+    ('G', 0b111100101101111),
+    ('H', 0b101101111101101),
+    ('I', 0b111010010010111),
+    ('J', 0b001001001101111),
+    ('K', 0b101101110101101),
+    ('L', 0b100100100100111),
+    ('M', 0b101111111101101),
+    ('N', 0b110101101101101),
+    ('O', 0b111101101101111),
+    ('P', 0b111101111100100),
+    ('Q', 0b111101101111001),
+    ('R', 0b111101110101101),
+    ('S', 0b111100111001111),
+    ('T', 0b111010010010010),
+    ('U', 0b101101101101111),
+    ('V', 0b101101101101010),
+    ('W', 0b101101111111101),
+    ('X', 0b101101010101101),
+    ('Y', 0b101101111010010),
+    ('Z', 0b111001010100111),
+    ('[', 0b110100100100110),
+    (']', 0b011001001001011),
+    ('_', 0b000000000000111),
+];
+
+fn glyph(c: char) -> u16 {
+    let find = |c: char| {
+        FONT.iter()
+            .find(|(known, _)| *known == c)
+            .map(|(_, bits)| *bits)
+    };
+    find(c.to_ascii_uppercase())
+        .or_else(|| find('?'))
+        .unwrap_or(0)
+}
+
+pub fn text_width(text: &str) -> usize {
+    (text.chars().count() * ADVANCE).saturating_sub(1)
+}
+
+// The lit pixels of `text`, relative to its top left corner.
+pub fn text_pixels(text: &str) -> Vec<(usize, usize)> {
+    let mut pixels = Vec::new();
+    for (index, c) in text.chars().enumerate() {
+        let bits = glyph(c);
+        for y in 0..GLYPH_HEIGHT {
+            for x in 0..GLYPH_WIDTH {
+                let bit = 14 - (y * GLYPH_WIDTH + x);
+                if bits & (1 << bit) != 0 {
+                    pixels.push((index * ADVANCE + x, y));
+                }
+            }
+        }
+    }
+    pixels
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub x: usize,
+    pub y: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Osd {
+    // Oldest first, with the frames left to show them.
+    messages: Vec<(String, u32)>,
+    // Frames and instructions per second, when the counters are on.
+    counters: Option<(u32, u32)>,
+    pub profile: String,
+    changed: bool,
+}
+
+impl Osd {
+    pub fn new(profile: &str) -> Osd {
+        Osd {
+            messages: Vec::new(),
+            counters: None,
+            profile: profile.to_string(),
+            changed: false,
+        }
+    }
+
+    pub fn show(&mut self, text: &str) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+        self.messages.push((text.to_string(), MESSAGE_FRAMES));
+        self.changed = true;
+    }
+
+    pub fn set_counters(&mut self, counters: Option<(u32, u32)>) {
+        if self.counters != counters {
+            self.counters = counters;
+            self.changed = true;
+        }
+    }
+
+    // Ages the messages by one frame. Returns whether the overlay changed
+    // since the last frame and has to be redrawn.
+    pub fn end_frame(&mut self) -> bool {
+        for (_, frames) in &mut self.messages {
+            *frames -= 1;
+        }
+        let count = self.messages.len();
+        self.messages.retain(|(_, frames)| *frames > 0);
+        let changed = self.changed || self.messages.len() != count;
+        self.changed = false;
+        changed
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        self.messages.iter().map(|(text, _)| text.as_str())
+    }
+
+    pub fn counters(&self) -> Option<String> {
+        self.counters
+            .map(|(fps, ips)| format!("{} FPS {} IPS", fps, ips))
+    }
+
+    // Everything at the bottom on one line, for frontends with little room.
+    pub fn status(&self) -> String {
+        match self.counters() {
+            Some(counters) => format!("{}  {}", self.profile, counters),
+            None => self.profile.clone(),
+        }
+    }
+
+    // The lines to draw on a screen `height` font pixels high.
+    pub fn labels(&self, height: usize) -> Vec<Label> {
+        let mut labels: Vec<Label> = self
+            .messages()
+            .enumerate()
+            .map(|(index, text)| Label {
+                x: 2,
+                y: 2 + index * LINE_HEIGHT,
+                text: text.to_string(),
+            })
+            .collect();
+        let bottom = height.saturating_sub(GLYPH_HEIGHT + 2);
+        if let Some(text) = self.counters() {
+            labels.push(Label {
+                x: 2,
+                y: bottom.saturating_sub(LINE_HEIGHT),
+                text,
+            });
+        }
+        labels.push(Label {
+            x: 2,
+            y: bottom,
+            text: self.profile.clone(),
+        });
+        labels
+    }
+}
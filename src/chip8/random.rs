@@ -0,0 +1,56 @@
+// Source of the bytes returned by `Cxkk`. The CPU only needs a byte at a time,
+// so embedded targets can plug in a hardware RNG or a cheap generator.
+pub trait RandomSource {
+    fn next_byte(&mut self) -> u8;
+}
+
+// Marsaglia's xorshift32; deterministic for a given seed and needs no std.
+pub struct XorShiftRandom {
+    state: u32,
+}
+
+impl XorShiftRandom {
+    pub fn new(seed: u32) -> XorShiftRandom {
+        XorShiftRandom {
+            state: if seed == 0 { 0x2545_F491 } else { seed },
+        }
+    }
+}
+
+impl RandomSource for XorShiftRandom {
+    fn next_byte(&mut self) -> u8 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 24) as u8
+    }
+}
+
+#[cfg(feature = "std")]
+pub struct ThreadRandom {
+    rng: rand::rngs::ThreadRng,
+}
+
+#[cfg(feature = "std")]
+impl ThreadRandom {
+    pub fn new() -> ThreadRandom {
+        ThreadRandom {
+            rng: rand::thread_rng(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for ThreadRandom {
+    fn default() -> ThreadRandom {
+        ThreadRandom::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl RandomSource for ThreadRandom {
+    fn next_byte(&mut self) -> u8 {
+        use rand::Rng;
+        self.rng.gen()
+    }
+}
@@ -0,0 +1,210 @@
+use super::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use alloc::vec;
+use alloc::vec::Vec;
+
+pub type Rgba = [u8; 4];
+
+// Colour 0 is the background and colour 1 the foreground. Colours 2 and 3 are
+// for a second bit plane and for pixels set in both planes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub colors: [Rgba; 4],
+    // Colour of the gaps between pixels when the grid is enabled.
+    pub grid: Rgba,
+}
+
+const fn rgb(value: u32) -> Rgba {
+    [(value >> 16) as u8, (value >> 8) as u8, value as u8, 255]
+}
+
+const fn theme(colors: [u32; 4], grid: u32) -> Palette {
+    Palette {
+        colors: [
+            rgb(colors[0]),
+            rgb(colors[1]),
+            rgb(colors[2]),
+            rgb(colors[3]),
+        ],
+        grid: rgb(grid),
+    }
+}
+
+pub const THEMES: [(&str, Palette); 6] = [
+    (
+        "classic",
+        theme([0x000000, 0xFFFFFF, 0xAAAAAA, 0x555555], 0x000000),
+    ),
+    (
+        "amber",
+        theme([0x1A0F00, 0xFFB000, 0xA86E00, 0xFFD980], 0x0D0800),
+    ),
+    (
+        "green-phosphor",
+        theme([0x001200, 0x33FF66, 0x1C9E3C, 0xB3FFC6], 0x000900),
+    ),
+    (
+        "lcd",
+        theme([0x9BBC0F, 0x0F380F, 0x306230, 0x8BAC0F], 0x8BAC0F),
+    ),
+    (
+        "high-contrast",
+        theme([0x000000, 0xFFFFFF, 0xFFFF00, 0x00FFFF], 0x000000),
+    ),
+    // Okabe-Ito colours, which stay distinct for the common colour vision
+    // deficiencies.
+    (
+        "colorblind-safe",
+        theme([0x000000, 0xE69F00, 0x56B4E9, 0xF0E442], 0x000000),
+    ),
+];
+
+impl Palette {
+    pub fn theme(name: &str) -> Option<Palette> {
+        THEMES
+            .iter()
+            .find(|(theme, _)| theme.eq_ignore_ascii_case(name))
+            .map(|(_, palette)| *palette)
+    }
+
+    pub fn background(&self) -> Rgba {
+        self.colors[0]
+    }
+
+    pub fn foreground(&self) -> Rgba {
+        self.colors[1]
+    }
+
+    pub fn color(&self, pixel: u8) -> Rgba {
+        self.colors[(pixel & 3) as usize]
+    }
+
+    // Fades from the background at level 0 to the colour of `pixel` at 255.
+    pub fn mix(&self, pixel: u8, level: u8) -> Rgba {
+        let background = self.background();
+        let color = self.color(pixel);
+        let mut mixed = [0; 4];
+        for (channel, value) in mixed.iter_mut().enumerate() {
+            let from = background[channel] as u32;
+            let to = color[channel] as u32;
+            *value = ((from * (255 - level as u32) + to * level as u32) / 255) as u8;
+        }
+        mixed
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        THEMES[0].1
+    }
+}
+
+// Parses `#RRGGBB` or `RRGGBB`.
+pub fn parse_color(text: &str) -> Option<Rgba> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(rgb)
+}
+
+// Where the screen goes inside a larger window: the top left corner and the
+// number of window pixels per CHIP-8 pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: usize,
+    pub y: usize,
+    pub scale: usize,
+}
+
+// Centres the screen at the largest integer scale that fits, so every CHIP-8
+// pixel has the same size. Windows smaller than 64x32 get a scale of 1.
+pub fn letterbox(width: usize, height: usize) -> Viewport {
+    letterbox_area(width, height, WIDTH, HEIGHT)
+}
+
+// Like `letterbox`, for an area of `columns` x `rows` CHIP-8 pixels.
+pub fn letterbox_area(width: usize, height: usize, columns: usize, rows: usize) -> Viewport {
+    let scale = (width / columns).min(height / rows).max(1);
+    Viewport {
+        x: width.saturating_sub(columns * scale) / 2,
+        y: height.saturating_sub(rows * scale) / 2,
+        scale,
+    }
+}
+
+// Turns the framebuffer into RGBA8 pixels, row-major without padding. Every
+// CHIP-8 pixel becomes a `scale` x `scale` square whose last `gap` rows and
+// columns are drawn in the grid colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rasterizer {
+    // At least 1, see `set_scale`.
+    scale: usize,
+    pub gap: usize,
+    pub palette: Palette,
+}
+
+impl Rasterizer {
+    pub fn new(scale: usize) -> Rasterizer {
+        assert!(scale > 0, "the scale must be at least 1");
+        Rasterizer {
+            scale,
+            gap: 0,
+            palette: Palette::default(),
+        }
+    }
+
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    // Panics on a scale of 0, which would render nothing.
+    pub fn set_scale(&mut self, scale: usize) {
+        assert!(scale > 0, "the scale must be at least 1");
+        self.scale = scale;
+    }
+
+    pub fn width(&self) -> usize {
+        WIDTH * self.scale
+    }
+
+    pub fn height(&self) -> usize {
+        HEIGHT * self.scale
+    }
+
+    pub fn buffer_len(&self) -> usize {
+        self.width() * self.height() * 4
+    }
+
+    pub fn render(&self, graphics: &Framebuffer) -> Vec<u8> {
+        let mut buffer = vec![0; self.buffer_len()];
+        self.render_into(graphics, &mut buffer);
+        buffer
+    }
+
+    pub fn render_into(&self, graphics: &Framebuffer, buffer: &mut [u8]) {
+        self.render_with(buffer, |x, y| self.palette.color(graphics.pixel(x, y)));
+    }
+
+    // Renders with the colour of every CHIP-8 pixel chosen by `color`.
+    pub fn render_with<F>(&self, buffer: &mut [u8], color: F)
+    where
+        F: Fn(usize, usize) -> Rgba,
+    {
+        assert_eq!(buffer.len(), self.buffer_len(), "Buffer has the wrong size");
+        let gap = self.gap.min(self.scale - 1);
+        let stride = self.width() * 4;
+
+        for (y, line) in buffer.chunks_exact_mut(stride).enumerate() {
+            let row = y / self.scale;
+            let in_gap_row = y % self.scale >= self.scale - gap;
+            for (x, pixel) in line.chunks_exact_mut(4).enumerate() {
+                let color = if in_gap_row || x % self.scale >= self.scale - gap {
+                    self.palette.grid
+                } else {
+                    color(x / self.scale, row)
+                };
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::framebuffer::Framebuffer;
+use super::raster::{Rasterizer, Rgba};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const TICKS_PER_SECOND: u64 = 60;
+
+// GIF delays are in hundredths of a second.
+fn centiseconds(ticks: u64) -> u64 {
+    (ticks * 100 + TICKS_PER_SECOND / 2) / TICKS_PER_SECOND
+}
+
+// Collects one framebuffer per 60 Hz tick and writes them as an animated GIF.
+// A frame that is identical to the previous one only extends its duration.
+pub struct GifRecorder {
+    rasterizer: Rasterizer,
+    frames: Vec<(Framebuffer, u64)>,
+}
+
+impl GifRecorder {
+    pub fn new(rasterizer: Rasterizer) -> GifRecorder {
+        GifRecorder {
+            rasterizer,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn capture(&mut self, graphics: &Framebuffer) {
+        match self.frames.last_mut() {
+            Some((last, ticks)) if last == graphics => *ticks += 1,
+            _ => self.frames.push((*graphics, 1)),
+        }
+    }
+
+    // Number of distinct frames, after merging.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.frames.iter().map(|(_, ticks)| ticks).sum()
+    }
+
+    fn palette(&self) -> [Rgba; 5] {
+        let palette = self.rasterizer.palette;
+        let [background, foreground, plane2, overlap] = palette.colors;
+        [background, foreground, plane2, overlap, palette.grid]
+    }
+
+    fn indexed_pixels(&self, graphics: &Framebuffer, palette: &[Rgba; 5]) -> Vec<u8> {
+        self.rasterizer
+            .render(graphics)
+            .chunks_exact(4)
+            .map(|pixel| {
+                palette
+                    .iter()
+                    .position(|color| color[..] == *pixel)
+                    .unwrap_or(0) as u8
+            })
+            .collect()
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), String> {
+        let palette = self.palette();
+        let global_palette: Vec<u8> = palette
+            .iter()
+            .flat_map(|color| color[..3].to_vec())
+            .collect();
+        let width = self.rasterizer.width() as u16;
+        let height = self.rasterizer.height() as u16;
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &global_palette).map_err(|e| e.to_string())?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+
+        // Rounding the start and end of every frame instead of its length keeps
+        // the total duration exact even though a tick is 1.67 centiseconds.
+        let mut elapsed = 0;
+        for (graphics, ticks) in &self.frames {
+            let start = centiseconds(elapsed);
+            elapsed += ticks;
+            let end = centiseconds(elapsed);
+
+            let pixels = self.indexed_pixels(graphics, &palette);
+            let mut frame = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+            frame.delay = (end - start).min(u16::MAX as u64) as u16;
+            encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        self.write(BufWriter::new(file))
+    }
+}
+
+pub fn recording_file(directory: &Path, frame: u64) -> PathBuf {
+    directory.join(format!("recording-{:08}.gif", frame))
+}
@@ -0,0 +1,46 @@
+use super::framebuffer::Framebuffer;
+use super::raster::Rasterizer;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub fn write_png<W: Write>(
+    writer: W,
+    graphics: &Framebuffer,
+    rasterizer: &Rasterizer,
+) -> Result<(), String> {
+    let mut encoder = png::Encoder::new(
+        writer,
+        rasterizer.width() as u32,
+        rasterizer.height() as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer
+        .write_image_data(&rasterizer.render(graphics))
+        .map_err(|e| e.to_string())
+}
+
+pub fn encode_png(graphics: &Framebuffer, rasterizer: &Rasterizer) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    write_png(&mut data, graphics, rasterizer)?;
+    Ok(data)
+}
+
+pub fn save_png(
+    path: &Path,
+    graphics: &Framebuffer,
+    rasterizer: &Rasterizer,
+) -> Result<(), String> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    }
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    write_png(BufWriter::new(file), graphics, rasterizer)
+}
+
+// Screenshots are named after the 60 Hz frame they were taken at.
+pub fn screenshot_file(directory: &Path, frame: u64) -> PathBuf {
+    directory.join(format!("screenshot-{:08}.png", frame))
+}
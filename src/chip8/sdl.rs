@@ -0,0 +1,230 @@
+use super::cpu::Cpu;
+use super::crt::CrtEffect;
+use super::frontend::{HostAction, InputSource};
+use super::keymap::{KeyMatch, Keymap};
+use super::keypad;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::mouse::MouseButton;
+use sdl2::EventPump;
+
+enum KeyActions {
+    Quit,
+    Break,
+    Screenshot,
+    ToggleRecording,
+    NextPalette,
+    ToggleFullscreen,
+    ToggleCrt(CrtEffect),
+    ToggleCounters,
+    KeyUpDown(usize),
+    None,
+}
+
+fn key_action(event: &Event, keymap: &Keymap) -> KeyActions {
+    match event {
+        Event::Quit { .. }
+        | Event::KeyDown {
+            keycode: Some(Keycode::Escape),
+            ..
+        } => KeyActions::Quit,
+        Event::KeyDown {
+            keycode: Some(Keycode::F5),
+            ..
+        } => KeyActions::Break,
+        Event::KeyDown {
+            keycode: Some(Keycode::F12),
+            ..
+        } => KeyActions::Screenshot,
+        Event::KeyDown {
+            keycode: Some(Keycode::F6),
+            ..
+        } => KeyActions::NextPalette,
+        Event::KeyDown {
+            keycode: Some(Keycode::F11),
+            ..
+        } => KeyActions::ToggleFullscreen,
+        Event::KeyDown {
+            keycode: Some(Keycode::F1),
+            ..
+        } => KeyActions::ToggleCrt(CrtEffect::Scanlines),
+        Event::KeyDown {
+            keycode: Some(Keycode::F2),
+            ..
+        } => KeyActions::ToggleCrt(CrtEffect::Grid),
+        Event::KeyDown {
+            keycode: Some(Keycode::F3),
+            ..
+        } => KeyActions::ToggleCrt(CrtEffect::Bloom),
+        Event::KeyDown {
+            keycode: Some(Keycode::F4),
+            ..
+        } => KeyActions::ToggleCrt(CrtEffect::Vignette),
+        Event::KeyDown {
+            keycode: Some(Keycode::F7),
+            ..
+        } => KeyActions::ToggleCounters,
+        Event::KeyDown {
+            keycode: Some(Keycode::F9),
+            ..
+        } => KeyActions::ToggleRecording,
+        Event::KeyDown {
+            keycode, scancode, ..
+        }
+        | Event::KeyUp {
+            keycode, scancode, ..
+        } => {
+            match key_name(*keycode, *scancode, keymap.matching).and_then(|name| keymap.key(&name))
+            {
+                Some(key) => KeyActions::KeyUpDown(key),
+                None => KeyActions::None,
+            }
+        }
+        _ => KeyActions::None,
+    }
+}
+
+fn key_name(
+    keycode: Option<Keycode>,
+    scancode: Option<Scancode>,
+    matching: KeyMatch,
+) -> Option<String> {
+    match matching {
+        KeyMatch::Scancode => scancode.map(|code| code.name().to_string()),
+        KeyMatch::Keycode => keycode.map(|code| code.name()),
+    }
+}
+
+// Opens a small window and prints the scancode and keycode of every key
+// pressed in it, and the hex key it presses, until the window is closed.
+pub fn capture_keys(keymap: &Keymap) -> Result<(), String> {
+    let sdl_context = sdl2::init()?;
+    let window = sdl_context
+        .video()?
+        .window("CHIP-8 - press keys, close to quit", 480, 120)
+        .position_centered()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+    canvas.clear();
+    canvas.present();
+    let mut event_pump = sdl_context.event_pump()?;
+    loop {
+        match event_pump.wait_event() {
+            Event::Quit { .. } => return Ok(()),
+            Event::KeyDown {
+                keycode,
+                scancode,
+                repeat: false,
+                ..
+            } => {
+                let scancode_name = key_name(keycode, scancode, KeyMatch::Scancode);
+                let keycode_name = key_name(keycode, scancode, KeyMatch::Keycode);
+                let hex_key = key_name(keycode, scancode, keymap.matching)
+                    .and_then(|name| keymap.key(&name))
+                    .map_or(String::from("none"), |key| format!("{:X}", key));
+                println!(
+                    "scancode {:?}  keycode {:?}  hex key {}",
+                    scancode_name.unwrap_or_default(),
+                    keycode_name.unwrap_or_default(),
+                    hex_key
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+// SDL only allows one event pump per context, so it lives as long as the
+// frontend instead of being created on every poll.
+pub struct SdlInput {
+    event_pump: EventPump,
+    keymap: Keymap,
+    mouse: MouseKeypad,
+}
+
+#[derive(Default)]
+struct MouseKeypad {
+    // The window size while the on-screen keypad is shown, to find the key
+    // under the mouse.
+    window: Option<(u32, u32)>,
+    clicked_key: Option<usize>,
+}
+
+impl SdlInput {
+    pub fn new(sdl_context: &sdl2::Sdl) -> Result<SdlInput, String> {
+        Ok(SdlInput {
+            event_pump: sdl_context.event_pump()?,
+            keymap: Keymap::default(),
+            mouse: MouseKeypad::default(),
+        })
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    pub fn show_keypad(&mut self, window_size: (u32, u32)) {
+        self.mouse.window = Some(window_size);
+    }
+}
+
+impl MouseKeypad {
+    // Clicking a key of the keypad holds it down until the button is
+    // released.
+    fn handle(&mut self, event: &Event, cpu: &mut Cpu) {
+        let window = match &mut self.window {
+            Some(window) => window,
+            None => return,
+        };
+        match *event {
+            Event::Window {
+                win_event: WindowEvent::SizeChanged(width, height),
+                ..
+            } => *window = (width as u32, height as u32),
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } if x >= 0 && y >= 0 => {
+                let (_, panel) = keypad::layout(window.0 as usize, window.1 as usize);
+                if let Some(key) = panel.key_at(x as usize, y as usize) {
+                    cpu.set_key(key, true);
+                    self.clicked_key = Some(key);
+                }
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => {
+                if let Some(key) = self.clicked_key.take() {
+                    cpu.set_key(key, false);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl InputSource for SdlInput {
+    fn poll(&mut self, cpu: &mut Cpu) -> HostAction {
+        for event in self.event_pump.poll_iter() {
+            let pressed = matches!(event, Event::KeyDown { .. });
+            self.mouse.handle(&event, cpu);
+            match key_action(&event, &self.keymap) {
+                KeyActions::Quit => return HostAction::Quit,
+                KeyActions::Break => return HostAction::Break,
+                KeyActions::Screenshot => return HostAction::Screenshot,
+                KeyActions::ToggleRecording => return HostAction::ToggleRecording,
+                KeyActions::NextPalette => return HostAction::NextPalette,
+                KeyActions::ToggleFullscreen => return HostAction::ToggleFullscreen,
+                KeyActions::ToggleCrt(effect) => return HostAction::ToggleCrt(effect),
+                KeyActions::ToggleCounters => return HostAction::ToggleCounters,
+                KeyActions::KeyUpDown(key) => cpu.set_key(key, pressed),
+                KeyActions::None => {}
+            }
+        }
+        HostAction::None
+    }
+}
@@ -1,6 +1,8 @@
-
+use super::frontend::AudioSink;
+#[cfg(feature = "audio")]
 use rodio::{OutputStream, OutputStreamHandle, Sink};
 
+#[cfg(feature = "audio")]
 pub fn build_sound_controller() -> Box<dyn SoundController> {
     match DefaultDeviceSoundController::new() {
         Ok(controller) => Box::new(controller),
@@ -8,17 +10,34 @@ pub fn build_sound_controller() -> Box<dyn SoundController> {
     }
 }
 
+#[cfg(not(feature = "audio"))]
+pub fn build_sound_controller() -> Box<dyn SoundController> {
+    Box::new(NoOpSoundController {})
+}
+
 pub trait SoundController {
     fn play(&self);
     fn stop(&self);
 }
 
+impl AudioSink for Box<dyn SoundController> {
+    fn set_tone(&mut self, playing: bool) {
+        if playing {
+            self.play();
+        } else {
+            self.stop();
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
 pub struct DefaultDeviceSoundController {
     _stream_handle: OutputStreamHandle,
     _stream: OutputStream,
     sink: Sink,
 }
 
+#[cfg(feature = "audio")]
 impl DefaultDeviceSoundController {
     pub fn new() -> Result<Self, String> {
         let res = rodio::OutputStream::try_default();
@@ -43,11 +62,12 @@ impl DefaultDeviceSoundController {
         Ok(DefaultDeviceSoundController {
             _stream: stream,
             _stream_handle: stream_handle,
-            sink: sink,
+            sink,
         })
     }
 }
 
+#[cfg(feature = "audio")]
 impl SoundController for DefaultDeviceSoundController {
     fn play(&self) {
         self.sink.play();
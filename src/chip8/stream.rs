@@ -0,0 +1,138 @@
+use super::framebuffer::Framebuffer;
+use super::frontend::{AudioSink, VideoSink};
+use super::headless::STEPS_PER_FRAME;
+use super::raster::Rasterizer;
+use std::io::Write;
+
+pub const FRAMES_PER_SECOND: usize = 60;
+pub const TONE_FREQUENCY: u64 = 800;
+const AMPLITUDE: i16 = 8192;
+
+// Writes one packed rgb24 image per 60 Hz frame, repeating the last presented
+// screen when nothing changed.
+pub struct RawVideo<W: Write> {
+    writer: W,
+    rasterizer: Rasterizer,
+    rgba: Vec<u8>,
+    rgb: Vec<u8>,
+    frames: usize,
+    error: Option<String>,
+}
+
+impl<W: Write> RawVideo<W> {
+    pub fn new(writer: W, rasterizer: Rasterizer) -> RawVideo<W> {
+        let mut video = RawVideo {
+            writer,
+            rgba: vec![0; rasterizer.buffer_len()],
+            rgb: Vec::new(),
+            rasterizer,
+            frames: 0,
+            error: None,
+        };
+        video.present(&Framebuffer::new());
+        video
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    // Flushes the writer and reports the first write error, if any.
+    pub fn finish(mut self) -> Result<W, String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush().map_err(|e| e.to_string())?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> VideoSink for RawVideo<W> {
+    fn present(&mut self, graphics: &Framebuffer) {
+        self.rasterizer.render_into(graphics, &mut self.rgba);
+        self.rgb = self
+            .rgba
+            .chunks_exact(4)
+            .flat_map(|pixel| pixel[..3].iter().copied())
+            .collect();
+    }
+
+    fn end_frame(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.writer.write_all(&self.rgb) {
+            Ok(()) => self.frames += 1,
+            Err(error) => self.error = Some(error.to_string()),
+        }
+    }
+}
+
+// Writes the buzzer as signed 16-bit little-endian mono samples, a square
+// wave while the tone is on and silence otherwise. Every step covers 1/360 of
+// a second so the audio stays in sync with `RawVideo`.
+pub struct PcmAudio<W: Write> {
+    writer: W,
+    sample_rate: u64,
+    playing: bool,
+    steps: u64,
+    samples: u64,
+    error: Option<String>,
+}
+
+impl<W: Write> PcmAudio<W> {
+    pub fn new(writer: W, sample_rate: u32) -> PcmAudio<W> {
+        PcmAudio {
+            writer,
+            sample_rate: sample_rate as u64,
+            playing: false,
+            steps: 0,
+            samples: 0,
+            error: None,
+        }
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn finish(mut self) -> Result<W, String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush().map_err(|e| e.to_string())?;
+        Ok(self.writer)
+    }
+
+    fn sample(&self, index: u64) -> i16 {
+        let half_periods = index * TONE_FREQUENCY * 2 / self.sample_rate;
+        match (self.playing, half_periods % 2) {
+            (false, _) => 0,
+            (true, 0) => AMPLITUDE,
+            (true, _) => -AMPLITUDE,
+        }
+    }
+}
+
+impl<W: Write> AudioSink for PcmAudio<W> {
+    fn set_tone(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    fn tick(&mut self) {
+        self.steps += 1;
+        if self.error.is_some() {
+            return;
+        }
+        // Counting from the start keeps rates that don't divide evenly exact.
+        let steps_per_second = (STEPS_PER_FRAME * FRAMES_PER_SECOND) as u64;
+        let end = self.steps * self.sample_rate / steps_per_second;
+        let bytes: Vec<u8> = (self.samples..end)
+            .flat_map(|index| self.sample(index).to_le_bytes())
+            .collect();
+        match self.writer.write_all(&bytes) {
+            Ok(()) => self.samples = end,
+            Err(error) => self.error = Some(error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use super::cpu::Cpu;
+use super::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use super::frontend::{AudioSink, HostAction, InputSource, VideoSink};
+use super::keymap::Keymap;
+use super::osd::Osd;
+use super::raster::{Palette, Rgba};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, execute, queue, style, terminal};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// Most terminals only report key presses, so a key counts as held until it
+// hasn't been repeated for this long.
+const KEY_HOLD: Duration = Duration::from_millis(150);
+
+// Two pixel rows per character cell: the upper half block draws the top pixel
+// and the lower half block the bottom one.
+pub fn half_blocks(graphics: &Framebuffer) -> Vec<String> {
+    (0..HEIGHT / 2)
+        .map(|row| {
+            (0..WIDTH)
+                .map(|x| (graphics.pixel(x, row * 2), graphics.pixel(x, row * 2 + 1)))
+                .map(|pixels| match pixels {
+                    (0, 0) => ' ',
+                    (_, 0) => '▀',
+                    (0, _) => '▄',
+                    _ => '█',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn keypad_key(code: KeyCode, keymap: &Keymap) -> Option<usize> {
+    match code {
+        KeyCode::Char(character) => keymap.key(&character.to_string()),
+        _ => None,
+    }
+}
+
+// Puts the terminal into raw mode on an alternate screen and restores it when
+// dropped, also when the emulator panics.
+pub struct TerminalSession {
+    key_releases: bool,
+}
+
+impl TerminalSession {
+    pub fn start() -> Result<TerminalSession, String> {
+        let key_releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        enter(key_releases).map_err(|e| e.to_string())?;
+        Ok(TerminalSession { key_releases })
+    }
+
+    pub fn input(&self) -> TerminalInput {
+        TerminalInput {
+            key_releases: self.key_releases,
+            pressed_at: [None; 16],
+            suspended: false,
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        let _ = leave(self.key_releases);
+    }
+}
+
+fn enter(key_releases: bool) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    if key_releases {
+        let flags = KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        execute!(stdout, PushKeyboardEnhancementFlags(flags))?;
+    }
+    Ok(())
+}
+
+fn leave(key_releases: bool) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if key_releases {
+        execute!(stdout, PopKeyboardEnhancementFlags)?;
+    }
+    execute!(
+        stdout,
+        style::ResetColor,
+        cursor::Show,
+        terminal::LeaveAlternateScreen
+    )?;
+    terminal::disable_raw_mode()
+}
+
+fn color(rgba: Rgba) -> style::Color {
+    style::Color::Rgb {
+        r: rgba[0],
+        g: rgba[1],
+        b: rgba[2],
+    }
+}
+
+// Draws in the terminal's own colours until a palette is set. Half blocks only
+// have two colours per cell, so the extra colours of a palette are unused.
+// The newest message of the on-screen display goes on the line below the
+// screen when the terminal has one.
+pub struct TerminalVideo {
+    graphics: Option<Framebuffer>,
+    // Whether the terminal shows `graphics` and the display line.
+    drawn: bool,
+    palette: Option<Palette>,
+    osd: Osd,
+}
+
+impl Default for TerminalVideo {
+    fn default() -> TerminalVideo {
+        TerminalVideo {
+            graphics: None,
+            drawn: false,
+            palette: None,
+            osd: Osd::new("CHIP-8"),
+        }
+    }
+}
+
+impl TerminalVideo {
+    pub fn new() -> TerminalVideo {
+        TerminalVideo::default()
+    }
+
+    fn draw(&self, graphics: &Framebuffer) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        if let Some(palette) = self.palette {
+            queue!(
+                stdout,
+                style::SetColors(style::Colors::new(
+                    color(palette.foreground()),
+                    color(palette.background())
+                ))
+            )?;
+        }
+        let lines = half_blocks(graphics);
+        for (row, line) in lines.iter().enumerate() {
+            queue!(stdout, cursor::MoveTo(0, row as u16), style::Print(line))?;
+        }
+        if terminal::size().is_ok_and(|(_, rows)| rows as usize > lines.len()) {
+            let text = self.osd.messages().last().map(String::from);
+            queue!(
+                stdout,
+                style::ResetColor,
+                cursor::MoveTo(0, lines.len() as u16),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                style::Print(text.unwrap_or_else(|| self.osd.status()))
+            )?;
+        }
+        stdout.flush()
+    }
+
+    fn draw_changes(&mut self) {
+        if let Some(graphics) = self.graphics.filter(|_| !self.drawn) {
+            self.draw(&graphics)
+                .expect("Unable to draw to the terminal");
+            self.drawn = true;
+        }
+    }
+}
+
+impl VideoSink for TerminalVideo {
+    fn present(&mut self, graphics: &Framebuffer) {
+        if self.graphics.as_ref() != Some(graphics) {
+            self.graphics = Some(*graphics);
+            self.drawn = false;
+        }
+    }
+
+    fn end_frame(&mut self) {
+        if self.osd.end_frame() {
+            self.drawn = false;
+        }
+        self.draw_changes();
+    }
+
+    fn refresh(&mut self) {
+        self.drawn = false;
+        self.draw_changes();
+    }
+
+    // The debugger prompt replaced the screen, so it is drawn again at the
+    // end of the next frame.
+    fn resume(&mut self) {
+        self.drawn = false;
+    }
+
+    fn set_palette(&mut self, palette: Palette) {
+        self.palette = Some(palette);
+        self.drawn = false;
+    }
+
+    fn osd(&mut self) -> Option<&mut Osd> {
+        Some(&mut self.osd)
+    }
+}
+
+// Rings the terminal bell whenever the buzzer turns on.
+#[derive(Default)]
+pub struct TerminalAudio {
+    playing: bool,
+}
+
+impl TerminalAudio {
+    pub fn new() -> TerminalAudio {
+        TerminalAudio::default()
+    }
+}
+
+impl AudioSink for TerminalAudio {
+    fn set_tone(&mut self, playing: bool) {
+        if playing && !self.playing {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+        self.playing = playing;
+    }
+}
+
+pub struct TerminalInput {
+    key_releases: bool,
+    pressed_at: [Option<Instant>; 16],
+    // Raw mode is switched off while the debugger prompt reads from stdin.
+    suspended: bool,
+    keymap: Keymap,
+}
+
+impl TerminalInput {
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    fn handle(&mut self, key: KeyEvent, cpu: &mut Cpu) -> HostAction {
+        let ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            _ if ctrl_c => return HostAction::Quit,
+            KeyCode::Esc if key.kind == KeyEventKind::Press => return HostAction::Quit,
+            KeyCode::F(5) if key.kind == KeyEventKind::Press => return HostAction::Break,
+            KeyCode::F(6) if key.kind == KeyEventKind::Press => return HostAction::NextPalette,
+            KeyCode::F(7) if key.kind == KeyEventKind::Press => return HostAction::ToggleCounters,
+            KeyCode::F(9) if key.kind == KeyEventKind::Press => return HostAction::ToggleRecording,
+            KeyCode::F(12) if key.kind == KeyEventKind::Press => return HostAction::Screenshot,
+            code => {
+                if let Some(index) = keypad_key(code, &self.keymap) {
+                    let pressed = key.kind != KeyEventKind::Release;
+                    self.pressed_at[index] = if pressed { Some(Instant::now()) } else { None };
+                    cpu.set_key(index, pressed);
+                }
+            }
+        }
+        HostAction::None
+    }
+}
+
+impl InputSource for TerminalInput {
+    fn poll(&mut self, cpu: &mut Cpu) -> HostAction {
+        if self.suspended {
+            enter(self.key_releases).expect("Unable to set up the terminal");
+            self.suspended = false;
+        }
+
+        while event::poll(Duration::from_secs(0)).unwrap_or(false) {
+            let action = match event::read() {
+                Ok(Event::Key(key)) => self.handle(key, cpu),
+                _ => HostAction::None,
+            };
+            if action != HostAction::None {
+                return action;
+            }
+        }
+
+        if !self.key_releases {
+            for (index, pressed_at) in self.pressed_at.iter_mut().enumerate() {
+                if pressed_at.is_some_and(|at| at.elapsed() > KEY_HOLD) {
+                    *pressed_at = None;
+                    cpu.set_key(index, false);
+                }
+            }
+        }
+        HostAction::None
+    }
+
+    fn suspend(&mut self) {
+        if !self.suspended {
+            let _ = leave(self.key_releases);
+            self.suspended = true;
+        }
+    }
+}
@@ -1 +1,6 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(feature = "sdl")]
 extern crate sdl2;
+pub mod chip8;
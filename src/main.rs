@@ -1,11 +1,460 @@
-pub mod chip8;
+use chip8::chip8::analysis::ControlFlowGraph;
+use chip8::chip8::config::{find_theme, Config, DEFAULT_CONFIG_FILE};
+use chip8::chip8::crt::CrtFilters;
+use chip8::chip8::debugger::format_state;
+use chip8::chip8::decompiler;
+use chip8::chip8::expression::Expression;
+use chip8::chip8::flicker::FlickerFilter;
+use chip8::chip8::headless::{Headless, InputScript, ScriptedInput};
+use chip8::chip8::keymap::{KeyMatch, Keymap};
+use chip8::chip8::lint;
+use chip8::chip8::raster::{Palette, Rasterizer};
+use chip8::chip8::recording::GifRecorder;
+use chip8::chip8::screenshot::save_png;
+use chip8::chip8::stream::{PcmAudio, RawVideo};
+use chip8::chip8::Emulator;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
 
-extern crate sdl2;
+fn usage() -> ! {
+    eprintln!(
+        "Usage: chip8 [--tui] [--scale N] [--fullscreen] [--flicker MODE] [--crt EFFECTS] [--fps] [--keypad] [--keycodes] [--break EXPR] [--watch EXPR] [--trace EXPR] [--cheat-dir DIR] [COLOURS] ROM"
+    );
+    eprintln!("       chip8 cfg ROM");
+    eprintln!("       chip8 decompile ROM");
+    eprintln!("       chip8 lint ROM");
+    eprintln!("       chip8 keys [--keycodes] [--config FILE]");
+    eprintln!("       chip8 headless [--frames N] [--until EXPR] [--input FILE] [--screenshot PNG] [--gif FILE] [--scale N] [COLOURS] ROM");
+    eprintln!("       chip8 stream [--frames N] [--input FILE] [--scale N] [--video FILE|-] [--audio FILE] [--sample-rate N] [COLOURS] ROM");
+    eprintln!("COLOURS: [--palette NAME] [--config FILE]");
+    process::exit(2);
+}
+
+// The `--palette` and `--config` options shared by all modes that draw.
+#[derive(Default)]
+struct ColourOptions {
+    palette: Option<String>,
+    config: Option<String>,
+}
+
+impl ColourOptions {
+    fn parse<'a>(&mut self, arg: &str, args: &mut impl Iterator<Item = &'a String>) -> bool {
+        let target = match arg {
+            "--palette" => &mut self.palette,
+            "--config" => &mut self.config,
+            _ => return false,
+        };
+        *target = Some(args.next().unwrap_or_else(|| usage()).clone());
+        true
+    }
+
+    // The config file is optional unless given explicitly.
+    fn load_config(&self) -> Config {
+        let config = match &self.config {
+            Some(filepath) => Config::load(Path::new(filepath)),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Config::load(Path::new(DEFAULT_CONFIG_FILE))
+            }
+            None => Ok(Config::default()),
+        };
+        config.unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(2);
+        })
+    }
+
+    // The named themes and the chosen palette.
+    fn resolve(&self) -> (Vec<(String, Palette)>, Palette) {
+        let config = self.load_config();
+        let result = config.themes().and_then(|themes| {
+            let palette = match &self.palette {
+                Some(name) => Some(find_theme(&themes, name)?),
+                None => config.palette(&themes)?,
+            };
+            Ok((themes, palette.unwrap_or_default()))
+        });
+        result.unwrap_or_else(|error: String| {
+            eprintln!("{}", error);
+            process::exit(2);
+        })
+    }
+}
+
+// Scales are whole numbers of 1 or more in every mode.
+fn parse_scale<T: FromStr + From<u8> + PartialOrd>(value: &str) -> T {
+    value
+        .parse()
+        .ok()
+        .filter(|scale| *scale >= T::from(1))
+        .unwrap_or_else(|| usage())
+}
+
+fn read_rom(filepath: &str) -> Vec<u8> {
+    fs::read(filepath).unwrap_or_else(|error| {
+        eprintln!("Unable to read {}: {}", filepath, error);
+        process::exit(1);
+    })
+}
 
-use std::env;
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let cartridge_filename = &args[1];
-    let mut chip8 = chip8::Emulator::initialize();
-    chip8.run_file(cartridge_filename).expect("Unable to start emulation.");
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("cfg") if args.len() == 2 => {
+            let graph = ControlFlowGraph::build(&read_rom(&args[1]));
+            print!("{}", graph.to_dot());
+        }
+        Some("decompile") if args.len() == 2 => {
+            let graph = ControlFlowGraph::build(&read_rom(&args[1]));
+            print!("{}", decompiler::decompile(&graph));
+        }
+        Some("lint") if args.len() == 2 => {
+            let lints = lint::lint(&ControlFlowGraph::build(&read_rom(&args[1])));
+            for lint in &lints {
+                println!("{}", lint);
+            }
+            if !lints.is_empty() {
+                process::exit(1);
+            }
+        }
+        Some("keys") => keys(&args[1..]),
+        Some("headless") => headless(&args[1..]),
+        Some("stream") => stream(&args[1..]),
+        _ => run(&args),
+    }
+}
+
+// Prints the names to use in the `[keymap]` section for the keys pressed.
+fn keys(args: &[String]) {
+    let mut colours = ColourOptions::default();
+    let mut keycodes = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--keycodes" => keycodes = true,
+            _ if colours.parse(arg, &mut args) => {}
+            _ => usage(),
+        }
+    }
+    let mut keymap = colours.load_config().keymap(None).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(2);
+    });
+    if keycodes {
+        keymap.matching = KeyMatch::Keycode;
+    }
+    if let Err(error) = capture_keys(&keymap) {
+        eprintln!("Unable to capture keys: {}", error);
+        process::exit(1);
+    }
+}
+
+fn headless(args: &[String]) {
+    let mut frames = 600;
+    let mut condition = None;
+    let mut input = InputScript::new();
+    let mut screenshot = None;
+    let mut gif = None;
+    let mut rasterizer = Rasterizer::new(1);
+    let mut colours = ColourOptions::default();
+    let mut cartridge_filename = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                frames = value.parse().unwrap_or_else(|_| usage());
+            }
+            "--until" => {
+                let source = args.next().unwrap_or_else(|| usage());
+                condition = Some(Expression::parse(source).unwrap_or_else(|error| {
+                    eprintln!("{}", error);
+                    process::exit(2);
+                }));
+            }
+            "--input" => {
+                input = read_input_script(args.next().unwrap_or_else(|| usage()));
+            }
+            "--screenshot" => {
+                screenshot = Some(args.next().unwrap_or_else(|| usage()));
+            }
+            "--gif" => {
+                gif = Some(args.next().unwrap_or_else(|| usage()));
+            }
+            "--scale" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                rasterizer.set_scale(parse_scale(value));
+            }
+            _ if colours.parse(arg, &mut args) => {}
+            _ if cartridge_filename.is_none() => cartridge_filename = Some(arg),
+            _ => usage(),
+        }
+    }
+    rasterizer.palette = colours.resolve().1;
+
+    let cartridge_filename = cartridge_filename.unwrap_or_else(|| usage());
+    let mut emulator = Headless::new(&read_rom(cartridge_filename));
+    emulator.set_input(input);
+    let mut recorder = GifRecorder::new(rasterizer);
+    let result = emulator.run_until(frames, |cpu| {
+        if gif.is_some() {
+            recorder.capture(&cpu.graphics);
+        }
+        condition
+            .as_ref()
+            .is_some_and(|condition| condition.is_true(cpu))
+    });
+
+    print!("{}", result.screen());
+    println!("frames={} {}", result.frames, format_state(&result.cpu));
+    if let Some(filepath) = screenshot {
+        if let Err(error) = save_png(Path::new(filepath), &result.graphics, &rasterizer) {
+            eprintln!("Unable to save {}: {}", filepath, error);
+            process::exit(1);
+        }
+    }
+    if let Some(filepath) = gif {
+        if let Err(error) = recorder.save(Path::new(filepath)) {
+            eprintln!("Unable to save {}: {}", filepath, error);
+            process::exit(1);
+        }
+    }
+    if condition.is_some() && !result.condition_met {
+        process::exit(1);
+    }
+}
+
+fn read_input_script(filepath: &str) -> InputScript {
+    let text = String::from_utf8_lossy(&read_rom(filepath)).into_owned();
+    InputScript::parse(&text).unwrap_or_else(|error| {
+        eprintln!("{}: {}", filepath, error);
+        process::exit(2);
+    })
+}
+
+fn create_output(filepath: &str) -> Box<dyn Write> {
+    if filepath == "-" {
+        return Box::new(BufWriter::new(io::stdout()));
+    }
+    match File::create(filepath) {
+        Ok(file) => Box::new(BufWriter::new(file)),
+        Err(error) => {
+            eprintln!("Unable to create {}: {}", filepath, error);
+            process::exit(1);
+        }
+    }
+}
+
+fn stream(args: &[String]) {
+    let mut frames = 600;
+    let mut input = InputScript::new();
+    let mut rasterizer = Rasterizer::new(1);
+    let mut colours = ColourOptions::default();
+    let mut video_filename = "-";
+    let mut audio_filename = None;
+    let mut sample_rate = 48000;
+    let mut cartridge_filename = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                frames = value.parse().unwrap_or_else(|_| usage());
+            }
+            "--input" => input = read_input_script(args.next().unwrap_or_else(|| usage())),
+            "--scale" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                rasterizer.set_scale(parse_scale(value));
+            }
+            "--video" => video_filename = args.next().unwrap_or_else(|| usage()),
+            "--audio" => audio_filename = Some(args.next().unwrap_or_else(|| usage())),
+            "--sample-rate" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                sample_rate = value
+                    .parse()
+                    .ok()
+                    .filter(|rate| *rate > 0)
+                    .unwrap_or_else(|| usage());
+            }
+            _ if colours.parse(arg, &mut args) => {}
+            _ if cartridge_filename.is_none() => cartridge_filename = Some(arg),
+            _ => usage(),
+        }
+    }
+    rasterizer.palette = colours.resolve().1;
+
+    let cartridge_filename = cartridge_filename.unwrap_or_else(|| usage());
+    let rom = read_rom(cartridge_filename);
+    let mut chip8 = Emulator::initialize();
+    chip8.steps_per_second = 0;
+    let mut video = RawVideo::new(create_output(video_filename), rasterizer);
+    let mut audio = PcmAudio::new(
+        match audio_filename {
+            Some(filepath) => create_output(filepath),
+            None => Box::new(io::sink()),
+        },
+        sample_rate,
+    );
+    let mut input = ScriptedInput::new(input, frames);
+
+    let result = chip8.run(&rom, &mut video, &mut audio, &mut input);
+    let written = video.frames();
+    let result = result
+        .and_then(|_| video.finish().map(|_| ()))
+        .and_then(|_| audio.finish().map(|_| ()));
+    if let Err(error) = result {
+        eprintln!("Unable to write the stream: {}", error);
+        process::exit(1);
+    }
+    eprintln!(
+        "{} frames of {}x{} rgb24 at 60 fps",
+        written,
+        rasterizer.width(),
+        rasterizer.height()
+    );
+}
+
+fn run(args: &[String]) {
+    let mut chip8 = Emulator::initialize();
+    let mut cartridge_filename = None;
+    let mut terminal = false;
+    let mut keycodes = false;
+    let mut colours = ColourOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let result = match arg.as_str() {
+            "--tui" => {
+                terminal = true;
+                continue;
+            }
+            "--flicker" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                chip8.flicker_filter = FlickerFilter::parse(value).unwrap_or_else(|error| {
+                    eprintln!("{}", error);
+                    process::exit(2);
+                });
+                continue;
+            }
+            "--crt" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                chip8.crt_filters = CrtFilters::parse(value).unwrap_or_else(|error| {
+                    eprintln!("{}", error);
+                    process::exit(2);
+                });
+                continue;
+            }
+            "--fullscreen" => {
+                chip8.fullscreen = true;
+                continue;
+            }
+            "--keypad" => {
+                chip8.show_keypad = true;
+                continue;
+            }
+            "--keycodes" => {
+                keycodes = true;
+                continue;
+            }
+            "--fps" => {
+                chip8.show_counters = true;
+                continue;
+            }
+            "--scale" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                chip8.window_scale = parse_scale(value);
+                continue;
+            }
+            "--break" | "--watch" | "--trace" => {
+                let expression = args.next().unwrap_or_else(|| usage());
+                match arg.as_str() {
+                    "--break" => chip8.debugger.add_breakpoint(expression),
+                    "--watch" => chip8.debugger.add_watchpoint(expression),
+                    _ => chip8.debugger.add_trace_filter(expression),
+                }
+            }
+            "--cheat-dir" => {
+                let directory = args.next().unwrap_or_else(|| usage());
+                chip8.cheat_directory = PathBuf::from(directory);
+                continue;
+            }
+            _ if colours.parse(arg, &mut args) => continue,
+            _ if cartridge_filename.is_none() => {
+                cartridge_filename = Some(arg);
+                continue;
+            }
+            _ => usage(),
+        };
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    }
+    (chip8.themes, chip8.palette) = colours.resolve();
+
+    let cartridge_filename = cartridge_filename.unwrap_or_else(|| usage());
+    let rom_name = Path::new(cartridge_filename)
+        .file_stem()
+        .map(|name| name.to_string_lossy());
+    chip8.keymap = colours
+        .load_config()
+        .keymap(rom_name.as_deref())
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(2);
+        });
+    if keycodes {
+        chip8.keymap.matching = KeyMatch::Keycode;
+    }
+    let result = if terminal {
+        start_terminal(&mut chip8, cartridge_filename)
+    } else {
+        start_window(&mut chip8, cartridge_filename)
+    };
+    if let Err(error) = result {
+        eprintln!("Unable to start emulation: {}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(feature = "sdl")]
+fn start_window(chip8: &mut Emulator, cartridge_filename: &str) -> Result<(), String> {
+    chip8.run_file(cartridge_filename)
+}
+
+#[cfg(not(feature = "sdl"))]
+fn start_window(_: &mut Emulator, _: &str) -> Result<(), String> {
+    Err(String::from(
+        "this build has no window support. Rebuild with `--features sdl`, or use `--tui` or `chip8 headless`.",
+    ))
+}
+
+#[cfg(feature = "sdl")]
+fn capture_keys(keymap: &Keymap) -> Result<(), String> {
+    chip8::chip8::sdl::capture_keys(keymap)
+}
+
+#[cfg(not(feature = "sdl"))]
+fn capture_keys(_: &Keymap) -> Result<(), String> {
+    Err(String::from(
+        "this build has no window support. Rebuild with `--features sdl`.",
+    ))
+}
+
+#[cfg(feature = "tui")]
+fn start_terminal(chip8: &mut Emulator, cartridge_filename: &str) -> Result<(), String> {
+    chip8.run_terminal(cartridge_filename)
+}
+
+#[cfg(not(feature = "tui"))]
+fn start_terminal(_: &mut Emulator, _: &str) -> Result<(), String> {
+    Err(String::from(
+        "this build has no terminal support. Rebuild with `--features tui`.",
+    ))
 }
@@ -0,0 +1,116 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::analysis::{ControlFlowGraph, EdgeKind, SelfModification};
+    use chip8::chip8::cpu::Instruction;
+
+    fn rom(words: &[u16]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn decode_matches_mnemonics() {
+        assert_eq!(Instruction::decode(0x00E0), Instruction::Cls);
+        assert_eq!(Instruction::decode(0x2345), Instruction::Call(0x345));
+        assert_eq!(Instruction::decode(0xD125), Instruction::Draw(1, 2, 5));
+        assert_eq!(Instruction::decode(0x5121), Instruction::Unknown(0x5121));
+        assert_eq!(Instruction::decode(0x8AB4).to_string(), "ADD VA, VB");
+        assert_eq!(Instruction::decode(0xF355).to_string(), "LD [I], V3");
+    }
+
+    #[test]
+    fn skip_and_loop_blocks() {
+        // 0x200: LD V0, 0
+        // 0x202: ADD V0, 1
+        // 0x204: SE V0, 10
+        // 0x206: JP 0x202
+        // 0x208: JP 0x208
+        let graph = ControlFlowGraph::build(&rom(&[0x6000, 0x7001, 0x300A, 0x1202, 0x1208]));
+
+        let starts: Vec<usize> = graph.blocks.keys().cloned().collect();
+        assert_eq!(starts, vec![0x200, 0x202, 0x206, 0x208]);
+
+        let skip = &graph.blocks[&0x202];
+        assert_eq!(skip.instructions.len(), 2);
+        assert_eq!(skip.successors.len(), 2);
+        assert_eq!(skip.successors[1].target, 0x208);
+        assert_eq!(skip.successors[1].kind, EdgeKind::Skip);
+
+        assert_eq!(graph.blocks[&0x206].successors[0].target, 0x202);
+        assert!(graph.unreachable.is_empty());
+    }
+
+    #[test]
+    fn calls_and_returns() {
+        // 0x200: CALL 0x206
+        // 0x202: JP 0x202
+        // 0x204: data
+        // 0x206: LD V1, 1
+        // 0x208: RET
+        let graph = ControlFlowGraph::build(&rom(&[0x2206, 0x1202, 0xFFFF, 0x6101, 0x00EE]));
+
+        assert!(graph.subroutines.contains(&0x206));
+        let call = &graph.blocks[&0x200];
+        assert_eq!(call.successors[0].kind, EdgeKind::Call);
+        assert_eq!(call.successors[1].target, 0x202);
+        assert!(graph.blocks[&0x206].successors.is_empty());
+        assert_eq!(graph.unreachable, vec![(0x204, 0x206)]);
+    }
+
+    #[test]
+    fn computed_jumps_are_unresolved() {
+        let graph = ControlFlowGraph::build(&rom(&[0x6002, 0xB300]));
+        assert_eq!(graph.computed_jumps, vec![0x202]);
+        assert!(graph.blocks[&0x200].successors.is_empty());
+        assert!(graph.to_dot().contains("JP V0, 0x300\\nunresolved"));
+    }
+
+    #[test]
+    fn writes_into_code_are_flagged() {
+        // 0x200: LD I, 0x208
+        // 0x202: LD B, V0
+        // 0x204: LD I, 0x300
+        // 0x206: LD [I], V2
+        // 0x208: JP 0x200
+        let graph = ControlFlowGraph::build(&rom(&[0xA208, 0xF033, 0xA300, 0xF255, 0x1200]));
+        assert_eq!(
+            graph.self_modifying,
+            vec![SelfModification {
+                address: 0x202,
+                target: 0x208,
+                length: 3
+            }]
+        );
+        assert_eq!(graph.known_i(0x206), Some(0x300));
+        assert_eq!(graph.known_i(0x200), None);
+        assert!(graph.to_dot().contains("color=red"));
+    }
+
+    #[test]
+    fn dot_export() {
+        let graph = ControlFlowGraph::build(&rom(&[0x2204, 0x1202, 0x00EE]));
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph chip8 {\n"));
+        assert!(dot.contains("b200 -> b204 [label=\"call\", style=dashed];"));
+        assert!(dot.contains("b202 -> b202 [label=\"jump\"];"));
+        assert!(dot.contains("b204 [label=\"0x204: RET\\l\", peripheries=2];"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn computed_jump_in_misaligned_block() {
+        // The call to 0x201 starts blocks at odd addresses that interleave
+        // with the even ones, so the block at 0x205 sits between 0x204 and
+        // the JP V0 at 0x208 at the end of the block at 0x204.
+        let rom = [
+            0x32, 0x37, 0x22, 0x01, 0xC1, 0xBA, 0x71, 0x67, 0xB2, 0x34, 0x1E, 0x68, 0xB2,
+        ];
+        let graph = ControlFlowGraph::build(&rom);
+        assert_eq!(graph.computed_jumps, vec![0x205, 0x208]);
+        assert_eq!(graph.block_containing(0x208).unwrap().start, 0x204);
+        assert!(graph.to_dot().contains("b204 -> u208 [style=dotted];"));
+    }
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::cheats::{
+        rom_hash, CheatEngine, CheatSet, Location, MemorySearch, SearchFilter,
+    };
+    use chip8::chip8::cpu::Cpu;
+
+    #[test]
+    fn narrow_down_by_change() {
+        let mut emu = Cpu::initialize();
+        emu.memory[0x300] = 3;
+        emu.memory[0x301] = 3;
+        emu.v[4] = 3;
+
+        let mut search = MemorySearch::new(&emu);
+        search.filter(&emu, SearchFilter::Equal(3));
+        assert_eq!(
+            search.candidates(),
+            &[
+                (Location::Memory(0x300), 3),
+                (Location::Memory(0x301), 3),
+                (Location::Register(4), 3)
+            ]
+        );
+
+        emu.memory[0x300] = 2;
+        emu.v[4] = 4;
+        assert_eq!(search.filter(&emu, SearchFilter::Changed), 2);
+
+        emu.memory[0x300] = 1;
+        emu.v[4] = 5;
+        assert_eq!(search.filter(&emu, SearchFilter::Decreased), 1);
+        assert_eq!(search.candidates(), &[(Location::Memory(0x300), 1)]);
+
+        assert_eq!(search.filter(&emu, SearchFilter::Unchanged), 1);
+        assert_eq!(search.filter(&emu, SearchFilter::Increased), 0);
+    }
+
+    #[test]
+    fn frozen_values_are_reapplied() {
+        let mut emu = Cpu::initialize();
+        let mut cheats = CheatSet::default();
+        cheats.freeze(Location::Register(3), 9);
+        cheats.freeze(Location::Memory(0x2F0), 5);
+
+        emu.cpu_step(0x6301);
+        assert_eq!(emu.v[3], 1);
+        cheats.apply(&mut emu);
+        assert_eq!(emu.v[3], 9);
+        assert_eq!(emu.memory[0x2F0], 5);
+
+        assert!(cheats.unfreeze(Location::Register(3)));
+        assert!(!cheats.unfreeze(Location::Register(3)));
+    }
+
+    #[test]
+    fn cheat_file_round_trip() {
+        let mut cheats = CheatSet::default();
+        cheats.freeze(Location::Memory(0x2F0), 0x63);
+        cheats.freeze(Location::Register(0xA), 1);
+
+        let text = cheats.to_string();
+        assert_eq!(text, "0x2F0 = 0x63\nvA = 0x01\n");
+        assert_eq!(CheatSet::parse(&text), Ok(cheats));
+        assert_eq!(
+            CheatSet::parse("# lives\nv3 = 7\n"),
+            Ok(CheatSet::parse("V3=0x07").unwrap())
+        );
+        assert_eq!(
+            CheatSet::parse("0X2F0 = 0X63"),
+            Ok(CheatSet::parse("0x2F0 = 0x63").unwrap())
+        );
+        assert!(CheatSet::parse("v3").is_err());
+        assert!(CheatSet::parse("0x1000 = 1").is_err());
+        assert!(CheatSet::parse("v3 = 256").is_err());
+    }
+
+    #[test]
+    fn cheats_are_stored_per_rom() {
+        // Unique per process, so parallel test runs don't share the files.
+        let directory = std::env::temp_dir().join(format!(
+            "chip8-cheats_are_stored_per_rom-{}",
+            std::process::id()
+        ));
+        let rom = [0x12, 0x00];
+        let mut emu = Cpu::initialize();
+
+        let mut engine = CheatEngine::new();
+        engine.load_for_rom(&directory, &rom).unwrap();
+        engine.execute("freeze", "v1 42", &mut emu);
+        assert_eq!(emu.v[1], 42);
+        engine.execute("save", "", &mut emu);
+
+        let mut reloaded = CheatEngine::new();
+        reloaded.load_for_rom(&directory, &rom).unwrap();
+        assert_eq!(reloaded.cheats, engine.cheats);
+
+        let mut other = CheatEngine::new();
+        other.load_for_rom(&directory, &[0x00, 0xE0]).unwrap();
+        assert!(other.cheats.freezes.is_empty());
+
+        assert_ne!(rom_hash(&rom), rom_hash(&[0x00, 0xE0]));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
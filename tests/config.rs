@@ -0,0 +1,104 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::config::{find_theme, Config};
+    use chip8::chip8::keymap::KeyMatch;
+    use chip8::chip8::raster::Palette;
+
+    #[test]
+    fn parses_sections_and_comments() {
+        let config = Config::parse(
+            "top = 1\n\n# comment\n[palette]\ntheme = \"amber\" # trailing\ngrid = \"#102030\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.get("", "top"), Some("1"));
+        assert_eq!(config.get("palette", "theme"), Some("amber"));
+        assert_eq!(config.get("palette", "grid"), Some("#102030"));
+        assert_eq!(config.get("palette", "missing"), None);
+
+        let error = Config::parse("[palette]\njust words\n").unwrap_err();
+        assert_eq!(error, "Line 2: expected `[SECTION]` or `KEY = VALUE`.");
+    }
+
+    #[test]
+    fn custom_themes_and_palette() {
+        let config = Config::parse(
+            "[theme.gameboy]\nbase = \"lcd\"\nforeground = \"#000000\"\n\n\
+             [palette]\ntheme = \"gameboy\"\nbackground = \"#FFFFFF\"\n",
+        )
+        .unwrap();
+        let themes = config.themes().unwrap();
+        assert_eq!(themes.len(), 7);
+
+        let lcd = Palette::theme("lcd").unwrap();
+        let gameboy = find_theme(&themes, "GameBoy").unwrap();
+        assert_eq!(gameboy.foreground(), [0, 0, 0, 255]);
+        assert_eq!(gameboy.colors[2], lcd.colors[2]);
+
+        let palette = config.palette(&themes).unwrap().unwrap();
+        assert_eq!(palette.background(), [255, 255, 255, 255]);
+        assert_eq!(palette.foreground(), [0, 0, 0, 255]);
+        assert_eq!(Config::default().palette(&themes), Ok(None));
+    }
+
+    #[test]
+    fn palette_errors() {
+        let themes = Config::default().themes().unwrap();
+        let error = find_theme(&themes, "sepia").unwrap_err();
+        assert!(error.starts_with("Unknown palette `sepia`. Available: classic, amber,"));
+
+        let config = Config::parse("[palette]\nforeground = \"#12345\"\n").unwrap();
+        assert_eq!(
+            config.palette(&themes),
+            Err(String::from(
+                "[palette]: `#12345` is not a `#RRGGBB` colour."
+            ))
+        );
+        let config = Config::parse("[theme.x]\nshadow = \"#123456\"\n").unwrap();
+        assert_eq!(
+            config.themes(),
+            Err(String::from("[theme.x]: Unknown key `shadow`."))
+        );
+    }
+
+    #[test]
+    fn keymap_with_rom_overrides() {
+        let config = Config::parse(
+            "[keymap]\n5 = [\"W\", \"Up\"]\n[keymap.pong]\n1 = \"Left Shift\"\n[keymap.other]\n1 = \"Space\"\n",
+        )
+        .unwrap();
+        let keymap = config.keymap(Some("pong")).unwrap();
+        assert_eq!(keymap.key("Up"), Some(0x5));
+        assert_eq!(keymap.key("Left Shift"), Some(0x1));
+        assert_eq!(keymap.key("Space"), None);
+        assert_eq!(config.keymap(None).unwrap().key("1"), Some(0x1));
+
+        let config = Config::parse(
+            "[keymap]\n5 = [\n  \"W\", # up\n  \"Up\",\n]\n\
+             [ keymap . \"Space Invaders [1978].v2\" ]\n1 = \"Left Shift\"\n",
+        )
+        .unwrap();
+        let keymap = config.keymap(Some("Space Invaders [1978].v2")).unwrap();
+        assert_eq!(keymap.key("W"), Some(0x5));
+        assert_eq!(keymap.key("Up"), Some(0x5));
+        assert_eq!(keymap.key("Left Shift"), Some(0x1));
+
+        let error = Config::parse("[keymap]\n5 = [\"W\",\n").unwrap_err();
+        assert_eq!(error, "Line 2: the array is not closed with `]`.");
+
+        let error = Config::parse("[keymap]\nz = \"A\"\n")
+            .unwrap()
+            .keymap(None)
+            .unwrap_err();
+        assert_eq!(error, "[keymap]: `z` is not a hex key from 0 to F.");
+
+        let keymap = Config::parse("[input]\nmatch = \"keycode\"\n")
+            .unwrap()
+            .keymap(None)
+            .unwrap();
+        assert_eq!(keymap.matching, KeyMatch::Keycode);
+    }
+}
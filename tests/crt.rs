@@ -0,0 +1,72 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::crt::{CrtEffect, CrtFilters};
+
+    fn image(width: usize, height: usize, value: u8) -> Vec<u8> {
+        (0..width * height)
+            .flat_map(|_| vec![value, value, value, 255])
+            .collect()
+    }
+
+    #[test]
+    fn parse_effects_and_strengths() {
+        let filters = CrtFilters::parse("scanlines:30,bloom").unwrap();
+        assert!(filters.is_enabled(CrtEffect::Scanlines));
+        assert_eq!(filters.strength(CrtEffect::Scanlines), 30);
+        assert!(filters.is_enabled(CrtEffect::Bloom));
+        assert_eq!(filters.strength(CrtEffect::Bloom), 40);
+        assert!(!filters.is_enabled(CrtEffect::Grid));
+        assert!(CrtFilters::parse("scanlines:200").is_err());
+        assert!(CrtFilters::parse("sepia").is_err());
+        assert!(!CrtFilters::parse("").unwrap().any());
+    }
+
+    #[test]
+    fn toggle_keeps_the_strength() {
+        let mut filters = CrtFilters::parse("vignette:20").unwrap();
+        assert!(!filters.toggle(CrtEffect::Vignette));
+        assert!(!filters.any());
+        assert!(filters.toggle(CrtEffect::Vignette));
+        assert_eq!(filters.strength(CrtEffect::Vignette), 20);
+    }
+
+    #[test]
+    fn scanlines_and_grid_darken_lines() {
+        let mut buffer = image(4, 4, 200);
+        CrtFilters::parse("scanlines:50")
+            .unwrap()
+            .apply(&mut buffer, 4, 4, 2);
+        assert_eq!(&buffer[0..4], &[200, 200, 200, 255]);
+        assert_eq!(&buffer[16..20], &[100, 100, 100, 255]);
+
+        let mut buffer = image(6, 6, 200);
+        CrtFilters::parse("grid:100")
+            .unwrap()
+            .apply(&mut buffer, 6, 6, 3);
+        assert_eq!(buffer[0], 200);
+        assert_eq!(buffer[2 * 4], 0);
+        assert_eq!(buffer[(2 * 6) * 4], 0);
+    }
+
+    #[test]
+    fn bloom_spreads_light_and_vignette_darkens_corners() {
+        let mut buffer = image(5, 5, 0);
+        buffer[(2 * 5 + 2) * 4] = 255;
+        CrtFilters::parse("bloom:100")
+            .unwrap()
+            .apply(&mut buffer, 5, 5, 2);
+        assert_eq!(buffer[(2 * 5 + 2) * 4], 255);
+        assert!(buffer[(2 * 5 + 3) * 4] > 0);
+        assert_eq!(buffer[0], 0);
+        assert_eq!(buffer[3], 255);
+
+        let mut buffer = image(8, 8, 200);
+        CrtFilters::parse("vignette:100")
+            .unwrap()
+            .apply(&mut buffer, 8, 8, 1);
+        assert!(buffer[0] < 120);
+        assert!(buffer[(4 * 8 + 4) * 4] > 190);
+    }
+}
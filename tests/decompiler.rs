@@ -0,0 +1,89 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::analysis::ControlFlowGraph;
+    use chip8::chip8::decompiler::decompile;
+
+    fn decompile_words(words: &[u16]) -> String {
+        let rom: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+        decompile(&ControlFlowGraph::build(&rom))
+    }
+
+    #[test]
+    fn if_else_from_skip_and_jump() {
+        // 0x200: SE V0, 1
+        // 0x202: JP 0x20A
+        // 0x204: LD V1, 2
+        // 0x206: LD V2, 3
+        // 0x208: JP 0x20C
+        // 0x20A: LD V1, 4
+        // 0x20C: JP 0x20C
+        let output = decompile_words(&[0x3001, 0x120A, 0x6102, 0x6203, 0x120C, 0x6104, 0x120C]);
+        assert_eq!(
+            output,
+            "fn main() {\n    if (v0 == 1) {\n        v1 = 2;\n        v2 = 3;\n    } else {\n        v1 = 4;\n    }\n    halt();\n}\n"
+        );
+    }
+
+    #[test]
+    fn single_statement_skip() {
+        // 0x200: SKNP V3
+        // 0x202: ADD V1, 0xFF
+        // 0x204: JP 0x204
+        let output = decompile_words(&[0xE3A1, 0x71FF, 0x1204]);
+        assert_eq!(
+            output,
+            "fn main() {\n    if (key(v3)) {\n        v1 -= 1;\n    }\n    halt();\n}\n"
+        );
+    }
+
+    #[test]
+    fn loops() {
+        // 0x200: LD V0, 0
+        // 0x202: ADD V0, 1        do {
+        // 0x204: SE V0, 10        } while (v0 != 10);
+        // 0x206: JP 0x202
+        // 0x208: SNE V0, 0        while (v0 != 0) {
+        // 0x20A: JP 0x210
+        // 0x20C: ADD V0, 0xFF
+        // 0x20E: JP 0x208         }
+        // 0x210: JP 0x210
+        let output = decompile_words(&[
+            0x6000, 0x7001, 0x300A, 0x1202, 0x4000, 0x1210, 0x70FF, 0x1208, 0x1210,
+        ]);
+        assert_eq!(
+            output,
+            "fn main() {\n    v0 = 0;\n    do {\n        v0 += 1;\n    } while (v0 != 10);\n    while (v0 != 0) {\n        v0 -= 1;\n    }\n    halt();\n}\n"
+        );
+    }
+
+    #[test]
+    fn subroutines() {
+        // 0x200: CALL 0x206
+        // 0x202: CALL 0x206
+        // 0x204: JP 0x204
+        // 0x206: CLS
+        // 0x208: RET
+        let output = decompile_words(&[0x2206, 0x2206, 0x1204, 0x00E0, 0x00EE]);
+        assert_eq!(
+            output,
+            "fn main() {\n    sub_206();\n    sub_206();\n    halt();\n}\n\nfn sub_206() {\n    clear();\n    return;\n}\n"
+        );
+    }
+
+    #[test]
+    fn unstructured_jumps_use_labels() {
+        // 0x200: LD V0, 1
+        // 0x202: JP 0x208
+        // 0x204: LD V1, 1
+        // 0x206: JP 0x206
+        // 0x208: JP 0x204
+        let output = decompile_words(&[0x6001, 0x1208, 0x6101, 0x1206, 0x1204]);
+        assert!(output.contains("    goto L_208;\n"));
+        assert!(output.contains("L_204:\n    v1 = 1;\n"));
+        assert!(output.contains("L_208:\n    goto L_204;\n"));
+    }
+}
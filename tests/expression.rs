@@ -0,0 +1,115 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::cpu::Cpu;
+    use chip8::chip8::debugger::{DebugEvent, Debugger};
+    use chip8::chip8::expression::Expression;
+
+    fn eval(source: &str, cpu: &Cpu) -> i64 {
+        Expression::parse(source)
+            .expect("Expression should parse")
+            .evaluate(cpu)
+    }
+
+    #[test]
+    fn registers_and_timers() {
+        let mut emu = Cpu::initialize();
+        emu.pc = 0x2F0;
+        emu.v[3] = 11;
+        emu.delay_timer = 0;
+
+        assert_eq!(eval("pc == 0x2F0 && v[3] > 10", &emu), 1);
+        assert_eq!(eval("v3 + vF", &emu), 11);
+        assert_eq!(eval("dt == 0", &emu), 1);
+        assert_eq!(eval("pc == 0x2F2 || v[3] < 10", &emu), 0);
+    }
+
+    #[test]
+    fn memory_indexed_by_i() {
+        let mut emu = Cpu::initialize();
+        emu.i = 0x300;
+        assert_eq!(eval("mem[i] != 0", &emu), 0);
+
+        emu.memory[0x300] = 7;
+        assert_eq!(eval("mem[i] != 0", &emu), 1);
+        assert_eq!(eval("mem[i + 1] == 0", &emu), 1);
+        assert_eq!(eval("mem[0x1000]", &emu), 0);
+    }
+
+    #[test]
+    fn precedence() {
+        let emu = Cpu::initialize();
+        assert_eq!(eval("1 + 2 * 3", &emu), 7);
+        assert_eq!(eval("(1 + 2) * 3", &emu), 9);
+        assert_eq!(eval("1 << 2 + 1", &emu), 8);
+        assert_eq!(eval("0xF0 & 0x3C | 1", &emu), 0x31);
+        assert_eq!(eval("0XF0 + 0B11", &emu), 0xF3);
+        assert_eq!(eval("!0 && -1 < 0", &emu), 1);
+        assert_eq!(eval("5 / 0", &emu), 0);
+    }
+
+    #[test]
+    fn parse_error_points_at_token() {
+        let error = Expression::parse("pc == && v[3]").unwrap_err();
+        assert_eq!(error.position, 6);
+        assert_eq!(error.length, 2);
+        assert_eq!(
+            error.to_string(),
+            "error: expected expression, found `&&`\n  pc == && v[3]\n        ^^"
+        );
+
+        let error = Expression::parse("foo > 1").unwrap_err();
+        assert_eq!(error.position, 0);
+        assert_eq!(error.message, "unknown identifier `foo`");
+
+        let error = Expression::parse("v[3").unwrap_err();
+        assert_eq!(error.position, 3);
+
+        let error = Expression::parse("1 2").unwrap_err();
+        assert_eq!(error.position, 2);
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints() {
+        let mut emu = Cpu::initialize();
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint("v[1] == 5").unwrap();
+        debugger.add_watchpoint("v[2]").unwrap();
+
+        assert!(debugger.after_step(&emu).is_empty());
+
+        emu.cpu_step(0x6105);
+        let events = debugger.after_step(&emu);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], DebugEvent::Breakpoint(0)));
+
+        emu.cpu_step(0x6209);
+        let events = debugger.after_step(&emu);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DebugEvent::Watchpoint {
+                index: 0,
+                old: 0,
+                new: 9
+            }
+        )));
+    }
+
+    #[test]
+    fn trace_filter() {
+        let mut emu = Cpu::initialize();
+        let mut debugger = Debugger::new();
+        debugger.add_trace_filter("pc >= 0x204").unwrap();
+
+        emu.cpu_step(0x6001);
+        assert!(debugger.after_step(&emu).is_empty());
+
+        emu.cpu_step(0x6002);
+        let events = debugger.after_step(&emu);
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].stops_execution());
+    }
+}
@@ -0,0 +1,62 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::flicker::{Deflicker, FlickerFilter};
+    use chip8::chip8::framebuffer::Framebuffer;
+    use chip8::chip8::raster::Palette;
+
+    #[test]
+    fn parse_modes() {
+        assert_eq!(FlickerFilter::parse("off"), Ok(FlickerFilter::Off));
+        assert_eq!(
+            FlickerFilter::parse("phosphor"),
+            Ok(FlickerFilter::Phosphor { persistence: 60 })
+        );
+        assert_eq!(
+            FlickerFilter::parse("blend:25"),
+            Ok(FlickerFilter::Blend { strength: 25 })
+        );
+        assert!(FlickerFilter::parse("blend:101").is_err());
+        assert!(FlickerFilter::parse("off:10").is_err());
+        assert!(FlickerFilter::parse("scanlines").is_err());
+    }
+
+    #[test]
+    fn phosphor_fades_over_frames() {
+        let mut deflicker = Deflicker::new(FlickerFilter::Phosphor { persistence: 50 });
+        let mut graphics = Framebuffer::new();
+        graphics.set(2, 1, true);
+        deflicker.push_frame(&graphics);
+        assert_eq!(deflicker.pixel(2, 1), (1, 255));
+
+        graphics.set(2, 1, false);
+        deflicker.push_frame(&graphics);
+        assert_eq!(deflicker.pixel(2, 1), (1, 127));
+        deflicker.push_frame(&graphics);
+        assert_eq!(deflicker.pixel(2, 1), (1, 63));
+        assert_eq!(deflicker.pixel(0, 0), (0, 0));
+    }
+
+    #[test]
+    fn blend_keeps_one_previous_frame() {
+        let mut deflicker = Deflicker::new(FlickerFilter::Blend { strength: 100 });
+        let mut graphics = Framebuffer::new();
+        graphics.set(0, 0, true);
+        deflicker.push_frame(&graphics);
+
+        graphics.set(0, 0, false);
+        deflicker.push_frame(&graphics);
+        assert_eq!(deflicker.pixel(0, 0), (1, 255));
+        deflicker.push_frame(&graphics);
+        assert_eq!(deflicker.pixel(0, 0).1, 0);
+    }
+
+    #[test]
+    fn mix_fades_between_background_and_colour() {
+        let palette = Palette::default();
+        assert_eq!(palette.mix(1, 0), [0, 0, 0, 255]);
+        assert_eq!(palette.mix(1, 255), [255, 255, 255, 255]);
+        assert_eq!(palette.mix(1, 51), [51, 51, 51, 255]);
+    }
+}
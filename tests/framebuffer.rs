@@ -0,0 +1,55 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::cpu::Cpu;
+    use chip8::chip8::framebuffer::Framebuffer;
+
+    #[test]
+    fn pixels_are_packed_left_to_right() {
+        let mut graphics = Framebuffer::new();
+        graphics.set(0, 3, true);
+        graphics.set(63, 3, true);
+        assert_eq!(graphics.row(3), 0x8000_0000_0000_0001);
+        assert_eq!(graphics.pixel(0, 3), 1);
+        assert_eq!(graphics.pixel(1, 3), 0);
+        assert_eq!(graphics.lit_pixels(), 2);
+
+        graphics.set(0, 3, false);
+        assert_eq!(graphics.row(3), 1);
+        graphics.clear();
+        assert_eq!(graphics, Framebuffer::default());
+    }
+
+    #[test]
+    fn sprite_rows_wrap_and_collide() {
+        let mut graphics = Framebuffer::new();
+        assert!(!graphics.draw_sprite_row(60, 0, 0b1111_0001));
+        assert_eq!(graphics.row(0), 0x1000_0000_0000_000F);
+
+        assert!(graphics.draw_sprite_row(124, 0, 0b0001_0000));
+        assert_eq!(graphics.row(0), 0x1000_0000_0000_000E);
+        assert!(!graphics.draw_sprite_row(8, 0, 0b0000_0001));
+    }
+
+    #[test]
+    fn drw_wraps_around_both_edges() {
+        let mut emu = Cpu::initialize();
+        emu.memory[0x300] = 0xC0;
+        emu.memory[0x301] = 0xC0;
+        emu.i = 0x300;
+        emu.v[0] = 63;
+        emu.v[1] = 31;
+
+        emu.cpu_step(0xD012);
+        assert_eq!(emu.v[0xF], 0);
+        for (x, y) in [(63, 31), (0, 31), (63, 0), (0, 0)] {
+            assert_eq!(emu.graphics.pixel(x, y), 1);
+        }
+        assert_eq!(emu.graphics.lit_pixels(), 4);
+
+        emu.cpu_step(0xD012);
+        assert_eq!(emu.v[0xF], 1);
+        assert_eq!(emu.graphics.lit_pixels(), 0);
+    }
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::cpu::Cpu;
+    use chip8::chip8::framebuffer::Framebuffer;
+    use chip8::chip8::frontend::{AudioSink, HostAction, InputSource, NoVideo, VideoSink};
+    use chip8::chip8::Emulator;
+    use std::path::PathBuf;
+
+    #[derive(Default)]
+    struct RecordingVideo {
+        frames: usize,
+        end_frames: usize,
+        lit: usize,
+    }
+
+    impl VideoSink for RecordingVideo {
+        fn present(&mut self, graphics: &Framebuffer) {
+            self.frames += 1;
+            self.lit = graphics.lit_pixels();
+        }
+
+        fn end_frame(&mut self) {
+            self.end_frames += 1;
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingAudio {
+        tones: Vec<bool>,
+    }
+
+    impl AudioSink for RecordingAudio {
+        fn set_tone(&mut self, playing: bool) {
+            self.tones.push(playing);
+        }
+    }
+
+    // Holds key 5 down and quits after a fixed number of polls.
+    struct ScriptedInput {
+        polls: usize,
+        limit: usize,
+    }
+
+    impl InputSource for ScriptedInput {
+        fn poll(&mut self, cpu: &mut Cpu) -> HostAction {
+            self.polls += 1;
+            cpu.set_key(5, true);
+            if self.polls > self.limit {
+                HostAction::Quit
+            } else {
+                HostAction::None
+            }
+        }
+    }
+
+    fn emulator() -> Emulator {
+        let mut emulator = Emulator::initialize();
+        emulator.steps_per_second = 0;
+        emulator.cheat_directory = PathBuf::from("target/no-cheats");
+        emulator
+    }
+
+    #[test]
+    fn run_loop_drives_all_frontends() {
+        // 0x200: LD V0, K
+        // 0x202: LD F, V0
+        // 0x204: DRW V1, V1, 5
+        // 0x206: LD V2, 2
+        // 0x208: LD ST, V2
+        // 0x20A: JP 0x20A
+        let rom = [
+            0xF0, 0x0A, 0xF0, 0x29, 0xD1, 0x15, 0x62, 0x02, 0xF2, 0x18, 0x12, 0x0A,
+        ];
+        let mut video = RecordingVideo::default();
+        let mut audio = RecordingAudio::default();
+        let mut input = ScriptedInput {
+            polls: 0,
+            limit: 10,
+        };
+
+        emulator()
+            .run(&rom, &mut video, &mut audio, &mut input)
+            .unwrap();
+
+        assert_eq!(video.frames, 1);
+        assert_eq!(video.end_frames, 1);
+        assert!(video.lit > 0);
+        assert_eq!(
+            audio.tones,
+            vec![false, false, false, false, false, true, false, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn unchanged_screen_is_not_presented() {
+        // 0x200: CLS
+        // 0x202: JP 0x202
+        let rom = [0x00, 0xE0, 0x12, 0x02];
+        let mut video = RecordingVideo::default();
+        let mut audio = RecordingAudio::default();
+        let mut input = ScriptedInput {
+            polls: 0,
+            limit: 60,
+        };
+
+        emulator()
+            .run(&rom, &mut video, &mut audio, &mut input)
+            .unwrap();
+
+        assert_eq!(video.frames, 1);
+        assert_eq!(video.end_frames, 10);
+    }
+
+    #[test]
+    fn frontends_can_be_trait_objects() {
+        let mut video: Box<dyn VideoSink> = Box::new(NoVideo);
+        let mut audio = RecordingAudio::default();
+        let mut input: Box<dyn InputSource> = Box::new(ScriptedInput { polls: 0, limit: 3 });
+
+        emulator()
+            .run(&[0x12, 0x00], video.as_mut(), &mut audio, input.as_mut())
+            .unwrap();
+        assert_eq!(audio.tones.len(), 4);
+    }
+}
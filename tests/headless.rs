@@ -0,0 +1,68 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::headless::{Headless, InputEvent, InputScript, STEPS_PER_FRAME};
+
+    fn rom(words: &[u16]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn runs_a_fixed_number_of_frames() {
+        // 0x200: ADD V0, 1
+        // 0x202: JP 0x200
+        let result = Headless::new(&rom(&[0x7001, 0x1200])).run_frames(10);
+        assert_eq!(result.frames, 10);
+        assert!(!result.condition_met);
+        assert_eq!(result.cpu.v[0] as usize, 10 * STEPS_PER_FRAME / 2);
+    }
+
+    #[test]
+    fn stops_when_the_condition_holds() {
+        let result = Headless::new(&rom(&[0x7001, 0x1200])).run_until(100, |cpu| cpu.v[0] >= 9);
+        assert!(result.condition_met);
+        assert_eq!(result.frames, 3);
+    }
+
+    #[test]
+    fn scripted_input_and_framebuffer() {
+        // 0x200: LD V0, K
+        // 0x202: LD F, V0
+        // 0x204: DRW V1, V1, 5
+        // 0x206: JP 0x206
+        let mut emulator = Headless::new(&rom(&[0xF00A, 0xF029, 0xD115, 0x1206]));
+        emulator.set_input(InputScript::parse("# press 1\n5 1 down\n6 1 up\n").unwrap());
+        let result = emulator.run_frames(10);
+
+        assert_eq!(result.cpu.v[0], 1);
+        assert!(!result.cpu.is_key_pressed(1));
+        let screen = result.screen();
+        let lines: Vec<&str> = screen.lines().map(|line| &line[..4]).collect();
+        assert_eq!(&lines[..5], &["..#.", ".##.", "..#.", "..#.", ".###"]);
+    }
+
+    #[test]
+    fn input_script_parsing() {
+        let script = InputScript::parse("10 a up\n2 F down").unwrap();
+        assert_eq!(
+            script.events(),
+            &[
+                InputEvent {
+                    frame: 2,
+                    key: 0xF,
+                    pressed: true
+                },
+                InputEvent {
+                    frame: 10,
+                    key: 0xA,
+                    pressed: false
+                },
+            ]
+        );
+        assert!(InputScript::parse("1 10 down").is_err());
+        assert!(InputScript::parse("1 a sideways").is_err());
+    }
+}
@@ -5,6 +5,7 @@ mod tests {
 
 
     use chip8::chip8::cpu::Cpu;
+    use chip8::chip8::random::XorShiftRandom;
     #[test]
     fn jp_addr() {
         let mut emu = Cpu::initialize();
@@ -304,4 +305,52 @@ mod tests {
         emu.cpu_step(0xCA00);
         assert_eq!(emu.v[0xA], 0);
     }
+
+    #[test]
+    fn skp_vx_uses_keypad_state() {
+        let mut emu = Cpu::initialize();
+        emu.v[0x3] = 0xA;
+
+        emu.set_key(0xA, true);
+        assert!(emu.is_key_pressed(0xA));
+        emu.cpu_step(0xE39E);
+        assert_eq!(emu.pc, 0x204);
+
+        emu.set_key(0xA, false);
+        emu.cpu_step(0xE39E);
+        assert_eq!(emu.pc, 0x206);
+    }
+
+    #[test]
+    fn rnd_vx_byte_uses_random_source() {
+        let mut first = Cpu::with_random(Box::new(XorShiftRandom::new(7)));
+        let mut second = Cpu::with_random(Box::new(XorShiftRandom::new(7)));
+
+        for _ in 0..8 {
+            first.cpu_step(0xC1FF);
+            second.cpu_step(0xC1FF);
+            assert_eq!(first.v[1], second.v[1]);
+        }
+        first.cpu_step(0xC10F);
+        assert_eq!(first.v[1] & 0xF0, 0);
+    }
+
+    #[test]
+    fn cls_and_drw_mark_the_screen_dirty() {
+        let mut emu = Cpu::initialize();
+        assert!(emu.take_dirty());
+        assert!(!emu.is_dirty());
+
+        emu.cpu_step(0x6105);
+        assert!(!emu.is_dirty());
+
+        emu.cpu_step(0xD005);
+        assert!(emu.take_dirty());
+        assert_eq!(emu.graphics.pixel(0, 0), 1);
+
+        let step_result = emu.cpu_step(0x00E0);
+        assert_eq!(step_result.graphics.pixel(0, 0), 0);
+        assert!(emu.take_dirty());
+        assert_eq!(emu.graphics.lit_pixels(), 0);
+    }
 }
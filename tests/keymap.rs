@@ -0,0 +1,44 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::keymap::{KeyMatch, Keymap};
+
+    #[test]
+    fn default_is_the_standard_layout() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.key("1"), Some(0x1));
+        assert_eq!(keymap.key("4"), Some(0xc));
+        assert_eq!(keymap.key("q"), Some(0x4));
+        assert_eq!(keymap.key("S"), Some(0x8));
+        assert_eq!(keymap.key("X"), Some(0x0));
+        assert_eq!(keymap.key("V"), Some(0xf));
+        assert_eq!(keymap.key("K"), None);
+        assert_eq!(keymap.names(0xa).collect::<Vec<_>>(), vec!["Z"]);
+    }
+
+    #[test]
+    fn several_keys_per_digit() {
+        let mut keymap = Keymap::default();
+        keymap.configure("5", r#"["W", "Up", ","]"#).unwrap();
+        assert_eq!(keymap.key("w"), Some(0x5));
+        assert_eq!(keymap.key("up"), Some(0x5));
+        assert_eq!(keymap.key(","), Some(0x5));
+
+        // Rebinding a name takes it away from its old digit.
+        keymap.configure("c", "Up").unwrap();
+        assert_eq!(keymap.key("Up"), Some(0xc));
+        assert_eq!(keymap.names(0x5).collect::<Vec<_>>(), vec!["W", ","]);
+        assert_eq!(keymap.key("4"), None);
+
+        assert!(keymap.configure("10", "A").is_err());
+        assert!(keymap.configure("g", "A").is_err());
+    }
+
+    #[test]
+    fn matches_scancodes_by_default() {
+        assert_eq!(Keymap::default().matching, KeyMatch::Scancode);
+        assert_eq!(KeyMatch::parse("keycode"), Ok(KeyMatch::Keycode));
+        assert!(KeyMatch::parse("layout").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::keypad::{layout, KeypadPanel};
+    use chip8::chip8::raster::Viewport;
+
+    #[test]
+    fn keypad_sits_right_of_the_screen() {
+        let (viewport, panel) = layout(768, 256);
+        assert_eq!(
+            viewport,
+            Viewport {
+                x: 0,
+                y: 0,
+                scale: 8
+            }
+        );
+        assert_eq!(
+            panel,
+            KeypadPanel {
+                x: 512,
+                y: 0,
+                cell: 64
+            }
+        );
+
+        // A wide window centres both.
+        let (viewport, panel) = layout(1000, 256);
+        assert_eq!(viewport.x, 116);
+        assert_eq!(panel.x, 628);
+    }
+
+    #[test]
+    fn keys_follow_the_cosmac_vip_layout() {
+        let (_, panel) = layout(768, 256);
+        assert_eq!(panel.key_at(520, 10), Some(0x1));
+        assert_eq!(panel.key_at(760, 10), Some(0xc));
+        assert_eq!(panel.key_at(600, 250), Some(0x0));
+        assert_eq!(panel.key_at(767, 255), Some(0xf));
+        assert_eq!(panel.key_at(500, 10), None);
+        assert_eq!(panel.key_at(780, 10), None);
+        assert_eq!(panel.key_position(0xe), (704, 128));
+        assert_eq!(panel.key_position(0xa), (512, 192));
+    }
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::analysis::ControlFlowGraph;
+    use chip8::chip8::lint::{lint, Lint};
+
+    fn lint_words(words: &[u16]) -> Vec<Lint> {
+        let rom: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+        lint(&ControlFlowGraph::build(&rom))
+    }
+
+    #[test]
+    fn clean_rom() {
+        // 0x200: CALL 0x204
+        // 0x202: JP 0x202
+        // 0x204: RET
+        assert!(lint_words(&[0x2204, 0x1202, 0x00EE]).is_empty());
+    }
+
+    #[test]
+    fn calls_and_returns() {
+        // 0x200: CALL 0x204
+        // 0x202: RET
+        // 0x204: JP 0x204
+        assert_eq!(
+            lint_words(&[0x2204, 0x00EE, 0x1204]),
+            vec![
+                Lint::CallWithoutReturn {
+                    address: 0x200,
+                    subroutine: 0x204
+                },
+                Lint::ReturnWithoutCall { address: 0x202 },
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_jumps() {
+        // 0x200: SE V0, 0
+        // 0x202: JP 0x201
+        // 0x204: JP 0xFFF
+        let lints = lint_words(&[0x3000, 0x1201, 0x1FFF]);
+        assert!(lints.contains(&Lint::MisalignedJump {
+            address: 0x202,
+            target: 0x201
+        }));
+        let outside = Lint::JumpOutsideProgram {
+            address: 0x204,
+            target: 0xFFF,
+        };
+        assert!(lints.contains(&outside));
+        assert_eq!(
+            outside.to_string(),
+            "0x204: jump to 0xFFF, outside of memory"
+        );
+    }
+
+    #[test]
+    fn misaligned_skip() {
+        // 0x200: SE V0, 0
+        // 0x202: JP 0x203
+        // 0x204: JP 0x204
+        // The skip lands on 0x204, inside the instruction at 0x203.
+        let lints = lint_words(&[0x3000, 0x1203, 0x1204]);
+        let skip = Lint::MisalignedSkip {
+            address: 0x200,
+            target: 0x204,
+        };
+        assert!(lints.contains(&skip));
+        assert!(!lints.contains(&Lint::MisalignedJump {
+            address: 0x200,
+            target: 0x204
+        }));
+        assert_eq!(
+            skip.to_string(),
+            "0x200: skip lands at 0x204, the middle of the instruction at 0x203"
+        );
+    }
+
+    #[test]
+    fn memory_accesses_through_i() {
+        // 0x200: LD I, 0xFFE
+        // 0x202: DRW V0, V1, 5
+        // 0x204: LD I, 0x208
+        // 0x206: LD [I], V1
+        // 0x208: JP 0x208
+        let lints = lint_words(&[0xAFFE, 0xD015, 0xA208, 0xF155, 0x1208]);
+        assert_eq!(
+            lints,
+            vec![
+                Lint::ReadPastMemory {
+                    address: 0x202,
+                    target: 0xFFE,
+                    length: 5
+                },
+                Lint::WriteOverlapsCode {
+                    address: 0x206,
+                    target: 0x208,
+                    length: 2
+                },
+            ]
+        );
+        assert_eq!(
+            lint_words(&[0x5121]),
+            vec![Lint::UnknownOpcode {
+                address: 0x200,
+                opcode: 0x5121
+            }]
+        );
+    }
+
+    // A chain of `depth` nested subroutines, each one calling the next.
+    fn call_chain(depth: u16) -> Vec<u16> {
+        let mut words = vec![0x2204, 0x1202];
+        for level in 1..depth {
+            words.push(0x2000 | (0x204 + level * 4));
+            words.push(0x00EE);
+        }
+        words.push(0x00E0);
+        words.push(0x00EE);
+        words
+    }
+
+    #[test]
+    fn stack_depth() {
+        assert_eq!(lint_words(&call_chain(15)), vec![]);
+        // The 16th call would write past the stack of the CPU.
+        assert_eq!(
+            lint_words(&call_chain(16)),
+            vec![Lint::StackOverflow {
+                address: 0x23C,
+                depth: 16
+            }]
+        );
+        assert_eq!(
+            lint_words(&call_chain(17)),
+            vec![Lint::StackOverflow {
+                address: 0x23C,
+                depth: 16
+            }]
+        );
+
+        // 0x200: CALL 0x204
+        // 0x202: JP 0x202
+        // 0x204: CALL 0x204
+        // 0x206: RET
+        assert_eq!(
+            lint_words(&[0x2204, 0x1202, 0x2204, 0x00EE]),
+            vec![Lint::Recursion {
+                address: 0x204,
+                subroutine: 0x204
+            }]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+// Builds the library without the `std` feature for a target that has no std at
+// all. Install it with `rustup target add thumbv7em-none-eabihf`.
+const TARGET: &str = "thumbv7em-none-eabihf";
+
+fn target_installed() -> bool {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let output = Command::new(rustc)
+        .args(["--print", "sysroot"])
+        .output()
+        .expect("Unable to run rustc");
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Path::new(&sysroot)
+        .join("lib/rustlib")
+        .join(TARGET)
+        .exists()
+}
+
+// Runs cargo on the library without default features, in its own target
+// directory so it doesn't wait for the lock of the outer build.
+fn cargo_without_std(command: &str, extra_args: &[&str]) -> bool {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    Command::new(cargo)
+        .current_dir(manifest_dir)
+        .args([command, "--lib", "--no-default-features"])
+        .args(extra_args)
+        .arg("--target-dir")
+        .arg(Path::new(manifest_dir).join("target").join("no_std"))
+        .status()
+        .expect("Unable to run cargo")
+        .success()
+}
+
+#[test]
+fn checks_without_std_on_host() {
+    assert!(cargo_without_std("check", &[]));
+}
+
+#[test]
+fn builds_for_bare_metal_target() {
+    assert!(
+        target_installed(),
+        "Target {} is not installed. Run `rustup target add {}`.",
+        TARGET,
+        TARGET
+    );
+    assert!(cargo_without_std("build", &["--target", TARGET]));
+}
@@ -0,0 +1,82 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::osd::{self, Label, Osd, MAX_MESSAGES, MESSAGE_FRAMES};
+
+    #[test]
+    fn messages_expire() {
+        let mut osd = Osd::new("CHIP-8");
+        assert!(!osd.end_frame());
+        osd.show("Paused");
+        assert!(osd.end_frame());
+        for _ in 2..MESSAGE_FRAMES {
+            assert!(!osd.end_frame());
+        }
+        assert_eq!(osd.messages().count(), 1);
+        assert!(osd.end_frame());
+        assert_eq!(osd.messages().count(), 0);
+    }
+
+    #[test]
+    fn only_the_newest_messages_are_kept() {
+        let mut osd = Osd::new("CHIP-8");
+        for index in 0..MAX_MESSAGES + 1 {
+            osd.show(&format!("Message {}", index));
+        }
+        assert_eq!(osd.messages().next(), Some("Message 1"));
+        assert_eq!(osd.messages().count(), MAX_MESSAGES);
+    }
+
+    #[test]
+    fn status_line_at_the_bottom() {
+        let mut osd = Osd::new("CHIP-8");
+        osd.show("Speed 200%");
+        osd.set_counters(Some((60, 360)));
+        assert_eq!(
+            osd.labels(64),
+            vec![
+                Label {
+                    x: 2,
+                    y: 2,
+                    text: String::from("Speed 200%"),
+                },
+                Label {
+                    x: 2,
+                    y: 50,
+                    text: String::from("60 FPS 360 IPS"),
+                },
+                Label {
+                    x: 2,
+                    y: 57,
+                    text: String::from("CHIP-8"),
+                },
+            ]
+        );
+        assert_eq!(osd.status(), "CHIP-8  60 FPS 360 IPS");
+    }
+
+    #[test]
+    fn profile_shown_without_counters() {
+        let osd = Osd::new("SUPER-CHIP");
+        assert_eq!(
+            osd.labels(64),
+            vec![Label {
+                x: 2,
+                y: 57,
+                text: String::from("SUPER-CHIP"),
+            }]
+        );
+        assert_eq!(osd.status(), "SUPER-CHIP");
+    }
+
+    #[test]
+    fn font_draws_glyphs() {
+        assert_eq!(osd::text_width("ab"), 7);
+        // `-` is the middle row of the glyph, in the second cell.
+        assert_eq!(osd::text_pixels(" -"), vec![(4, 2), (5, 2), (6, 2)]);
+        // Lower case uses the upper case glyphs.
+        assert_eq!(osd::text_pixels("a"), osd::text_pixels("A"));
+        assert_eq!(osd::text_pixels("~"), osd::text_pixels("?"));
+    }
+}
@@ -0,0 +1,107 @@
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::framebuffer::Framebuffer;
+    use chip8::chip8::raster::{letterbox, parse_color, Palette, Rasterizer, Viewport, THEMES};
+
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    fn pixel(buffer: &[u8], rasterizer: &Rasterizer, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * rasterizer.width() + x) * 4;
+        [
+            buffer[offset],
+            buffer[offset + 1],
+            buffer[offset + 2],
+            buffer[offset + 3],
+        ]
+    }
+
+    #[test]
+    fn scales_pixels() {
+        let mut graphics = Framebuffer::new();
+        graphics.set(2, 1, true);
+        let rasterizer = Rasterizer::new(3);
+        let buffer = rasterizer.render(&graphics);
+
+        assert_eq!((rasterizer.width(), rasterizer.height()), (192, 96));
+        assert_eq!(buffer.len(), 192 * 96 * 4);
+        assert_eq!(pixel(&buffer, &rasterizer, 6, 3), WHITE);
+        assert_eq!(pixel(&buffer, &rasterizer, 8, 5), WHITE);
+        assert_eq!(pixel(&buffer, &rasterizer, 9, 5), BLACK);
+        assert_eq!(pixel(&buffer, &rasterizer, 6, 6), BLACK);
+    }
+
+    #[test]
+    fn palette_and_grid_gaps() {
+        let mut graphics = Framebuffer::new();
+        graphics.set(0, 0, true);
+        let mut rasterizer = Rasterizer::new(4);
+        rasterizer.gap = 1;
+        rasterizer.palette = Palette {
+            colors: [
+                [10, 20, 30, 255],
+                [200, 100, 0, 255],
+                [0, 0, 0, 255],
+                [0, 0, 0, 255],
+            ],
+            grid: [1, 2, 3, 4],
+        };
+        let mut buffer = vec![0; rasterizer.buffer_len()];
+        rasterizer.render_into(&graphics, &mut buffer);
+
+        assert_eq!(pixel(&buffer, &rasterizer, 0, 0), [200, 100, 0, 255]);
+        assert_eq!(pixel(&buffer, &rasterizer, 2, 2), [200, 100, 0, 255]);
+        assert_eq!(pixel(&buffer, &rasterizer, 3, 0), [1, 2, 3, 4]);
+        assert_eq!(pixel(&buffer, &rasterizer, 0, 3), [1, 2, 3, 4]);
+        assert_eq!(pixel(&buffer, &rasterizer, 4, 0), [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn set_scale_changes_the_size() {
+        let mut rasterizer = Rasterizer::new(1);
+        rasterizer.set_scale(3);
+        assert_eq!(rasterizer.scale(), 3);
+        assert_eq!(
+            rasterizer.render(&Framebuffer::new()).len(),
+            64 * 3 * 32 * 3 * 4
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_scale_panics() {
+        Rasterizer::new(0);
+    }
+
+    #[test]
+    fn themes_have_four_colours() {
+        let mut graphics = Framebuffer::new();
+        graphics.set(1, 0, true);
+        let mut rasterizer = Rasterizer::new(1);
+        rasterizer.palette = Palette::theme("Amber").unwrap();
+        let buffer = rasterizer.render(&graphics);
+
+        for (x, color) in rasterizer.palette.colors.iter().take(2).enumerate() {
+            assert_eq!(pixel(&buffer, &rasterizer, x, 0), *color);
+        }
+        for (value, color) in rasterizer.palette.colors.iter().enumerate() {
+            assert_eq!(rasterizer.palette.color(value as u8), *color);
+        }
+        assert_eq!(parse_color("#FFB000"), Some([255, 176, 0, 255]));
+        assert_eq!(rasterizer.palette.foreground(), [255, 176, 0, 255]);
+        assert_eq!(parse_color("FFB00"), None);
+        assert!(Palette::theme("sepia").is_none());
+        assert_eq!(THEMES.len(), 6);
+    }
+
+    #[test]
+    fn letterbox_keeps_integer_scale() {
+        let fit = |x, y, scale| Viewport { x, y, scale };
+        assert_eq!(letterbox(512, 256), fit(0, 0, 8));
+        assert_eq!(letterbox(3840, 2160), fit(0, 120, 60));
+        assert_eq!(letterbox(700, 300), fit(62, 6, 9));
+        assert_eq!(letterbox(40, 20), fit(0, 0, 1));
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::framebuffer::Framebuffer;
+    use chip8::chip8::raster::Rasterizer;
+    use chip8::chip8::recording::{recording_file, GifRecorder};
+    use std::path::Path;
+
+    fn decode(data: &[u8]) -> Vec<gif::Frame<'static>> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(data).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push(frame.clone());
+        }
+        frames
+    }
+
+    #[test]
+    fn identical_frames_are_merged() {
+        let mut recorder = GifRecorder::new(Rasterizer::new(2));
+        let blank = Framebuffer::new();
+        let mut lit = blank;
+        lit.set(0, 0, true);
+        for graphics in [&blank, &blank, &lit, &lit, &lit, &blank] {
+            recorder.capture(graphics);
+        }
+        assert_eq!(recorder.frame_count(), 3);
+        assert_eq!(recorder.ticks(), 6);
+
+        let mut data = Vec::new();
+        recorder.write(&mut data).unwrap();
+        let frames = decode(&data);
+        assert_eq!(frames.len(), 3);
+        assert_eq!((frames[1].width, frames[1].height), (128, 64));
+        assert_eq!(&frames[1].buffer[0..3], &[1, 1, 0]);
+        assert_eq!(frames[0].buffer[0], 0);
+    }
+
+    #[test]
+    fn delays_add_up_to_recorded_time() {
+        let mut recorder = GifRecorder::new(Rasterizer::new(1));
+        let mut graphics = Framebuffer::new();
+        for tick in 0..60 {
+            graphics.set(tick, 0, true);
+            recorder.capture(&graphics);
+        }
+
+        let mut data = Vec::new();
+        recorder.write(&mut data).unwrap();
+        let delays: Vec<u16> = decode(&data).iter().map(|frame| frame.delay).collect();
+        assert_eq!(delays.len(), 60);
+        assert_eq!(delays.iter().map(|delay| *delay as u32).sum::<u32>(), 100);
+        assert!(delays.iter().all(|delay| *delay == 1 || *delay == 2));
+    }
+
+    #[test]
+    fn file_name() {
+        let path = recording_file(Path::new("recordings"), 7);
+        assert!(path.ends_with("recording-00000007.gif"));
+    }
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::framebuffer::Framebuffer;
+    use chip8::chip8::raster::{Palette, Rasterizer};
+    use chip8::chip8::screenshot::{encode_png, save_png, screenshot_file};
+    use std::fs;
+    use std::path::Path;
+
+    fn decode(data: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let decoder = png::Decoder::new(data);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        (info, pixels)
+    }
+
+    #[test]
+    fn native_resolution_with_palette() {
+        let mut graphics = Framebuffer::new();
+        graphics.set(1, 0, true);
+        let mut rasterizer = Rasterizer::new(1);
+        rasterizer.palette = Palette {
+            colors: [
+                [0, 0, 64, 255],
+                [255, 128, 0, 255],
+                [0, 0, 0, 255],
+                [0, 0, 0, 255],
+            ],
+            grid: [0, 0, 0, 255],
+        };
+
+        let (info, pixels) = decode(&encode_png(&graphics, &rasterizer).unwrap());
+        assert_eq!((info.width, info.height), (64, 32));
+        assert_eq!(&pixels[0..4], &[0, 0, 64, 255]);
+        assert_eq!(&pixels[4..8], &[255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn scaled_file() {
+        let graphics = Framebuffer::from_rows([u64::MAX; 32]);
+        let path = screenshot_file(Path::new("target/test-screenshots"), 42);
+        assert!(path.ends_with("screenshot-00000042.png"));
+
+        save_png(&path, &graphics, &Rasterizer::new(4)).unwrap();
+        let (info, pixels) = decode(&fs::read(&path).unwrap());
+        assert_eq!((info.width, info.height), (256, 128));
+        assert!(pixels.iter().all(|byte| *byte == 255));
+    }
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "std")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::headless::{InputScript, ScriptedInput};
+    use chip8::chip8::raster::Rasterizer;
+    use chip8::chip8::stream::{PcmAudio, RawVideo};
+    use chip8::chip8::Emulator;
+    use std::path::PathBuf;
+
+    fn stream(rom: &[u8], script: InputScript, frames: usize) -> (Vec<u8>, Vec<i16>) {
+        let mut emulator = Emulator::initialize();
+        emulator.steps_per_second = 0;
+        emulator.cheat_directory = PathBuf::from("target/no-cheats");
+        let mut video = RawVideo::new(Vec::new(), Rasterizer::new(1));
+        let mut audio = PcmAudio::new(Vec::new(), 44100);
+        let mut input = ScriptedInput::new(script, frames);
+
+        emulator
+            .run(rom, &mut video, &mut audio, &mut input)
+            .unwrap();
+        assert_eq!(video.frames(), frames);
+        let samples = audio
+            .finish()
+            .unwrap()
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect();
+        (video.finish().unwrap(), samples)
+    }
+
+    #[test]
+    fn audio_and_video_cover_the_same_time() {
+        // 0x200: LD V0, 0x0F
+        // 0x202: LD F, V0
+        // 0x204: DRW V1, V1, 5
+        // 0x206: LD V2, 2
+        // 0x208: LD ST, V2
+        // 0x20A: JP 0x20A
+        let rom = [
+            0x60, 0x0F, 0xF0, 0x29, 0xD1, 0x15, 0x62, 0x02, 0xF2, 0x18, 0x12, 0x0A,
+        ];
+        let (video, samples) = stream(&rom, InputScript::new(), 3);
+
+        assert_eq!(video.len(), 3 * 64 * 32 * 3);
+        assert_eq!(&video[0..3], &[255, 255, 255]);
+        // 44100 Hz doesn't divide into 360 steps per second, but three frames
+        // are exactly 1/20 of a second.
+        assert_eq!(samples.len(), 2205);
+        assert!(samples[..1000].iter().any(|sample| *sample != 0));
+        assert!(samples[1000..].iter().all(|sample| *sample == 0));
+    }
+
+    #[test]
+    fn scripted_input_presses_keys_at_frames() {
+        // 0x200: LD V0, K
+        // 0x202: LD ST, V0
+        // 0x204: JP 0x204
+        let rom = [0xF0, 0x0A, 0xF0, 0x18, 0x12, 0x04];
+        let mut script = InputScript::new();
+        script.press(2, 9);
+        script.release(3, 9);
+        let (_, samples) = stream(&rom, script, 5);
+
+        assert_eq!(samples.len(), 3675);
+        assert!(samples[..1470].iter().all(|sample| *sample == 0));
+        assert!(samples[1470..].iter().any(|sample| *sample != 0));
+    }
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "tui")]
+
+extern crate chip8;
+
+#[cfg(test)]
+mod tests {
+    use chip8::chip8::framebuffer::Framebuffer;
+    use chip8::chip8::terminal::half_blocks;
+
+    #[test]
+    fn two_pixel_rows_per_line() {
+        let mut graphics = Framebuffer::new();
+        graphics.set(0, 0, true);
+        graphics.set(1, 1, true);
+        graphics.set(2, 0, true);
+        graphics.set(2, 1, true);
+        graphics.set(63, 31, true);
+
+        let lines = half_blocks(&graphics);
+        assert_eq!(lines.len(), 16);
+        assert!(lines.iter().all(|line| line.chars().count() == 64));
+        assert!(lines[0].starts_with("▀▄█ "));
+        assert!(lines[15].ends_with(" ▄"));
+    }
+}